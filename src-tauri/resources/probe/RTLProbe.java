@@ -0,0 +1,11 @@
+// 启动前的JVM自检探针，由 utils/test_java.rs 嵌入并运行
+// 编译: javac -encoding UTF-8 --release 8 RTLProbe.java
+public class RTLProbe {
+    public static void main(String[] args) {
+        System.out.println("java.version=" + System.getProperty("java.version"));
+        System.out.println("java.vendor=" + System.getProperty("java.vendor"));
+        System.out.println("os.arch=" + System.getProperty("os.arch"));
+        System.out.println("data.model=" + System.getProperty("sun.arch.data.model"));
+        System.out.println("max.memory=" + Runtime.getRuntime().maxMemory());
+    }
+}
@@ -0,0 +1,232 @@
+// ***
+// 启动器设置
+// ***
+
+use crate::utils::home_dir;
+use serde::{Deserialize, Serialize};
+use std::env::consts::OS;
+use std::path::{Path, PathBuf};
+
+const SETTING_FILE: &str = "settings.json";
+
+// 与启动器可执行文件同目录的便携模式标记文件
+const PORTABLE_MARKER: &str = "rtlauncher.portable";
+
+// 已登记的游戏目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameDirectory {
+    pub name: String,
+    pub path: String,
+}
+
+// 游戏目录列表，返回给前端
+#[derive(Debug, Clone, Serialize)]
+pub struct GameDirectories {
+    pub dirs: Vec<GameDirectory>,
+    pub selected: String,
+}
+
+// 启动器设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LauncherSetting {
+    pub game_dirs: Vec<GameDirectory>,
+    pub selected_game_dir: Option<String>,
+    pub redact_uuid: bool, // 日志与崩溃报告中是否隐藏账号uuid
+}
+
+impl LauncherSetting {
+    // 读取设置，文件不存在时使用默认值
+    pub fn load() -> Self {
+        let mut setting: Self = std::fs::read_to_string(get_launcher_dir().join(SETTING_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        // 保证至少有一个默认游戏目录
+        if setting.game_dirs.is_empty() {
+            setting.game_dirs.push(GameDirectory {
+                name: "默认".to_string(),
+                path: to_stored_path(&get_default_game_dir()),
+            });
+        }
+        setting
+    }
+
+    // 保存设置
+    pub fn save(&self) -> std::io::Result<()> {
+        let launcher_dir = get_launcher_dir();
+        std::fs::create_dir_all(&launcher_dir)?;
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(launcher_dir.join(SETTING_FILE), content)
+    }
+
+    // 当前选中的游戏目录，按保存时的形式返回
+    pub fn selected_path(&self) -> Option<&String> {
+        self.selected_game_dir
+            .as_ref()
+            .filter(|selected| self.game_dirs.iter().any(|dir| &dir.path == *selected))
+            .or_else(|| self.game_dirs.first().map(|dir| &dir.path))
+    }
+
+    // 当前选中的游戏目录
+    pub fn current_game_dir(&self) -> PathBuf {
+        self.selected_path()
+            .map(|path| resolve_stored_path(path))
+            .unwrap_or_else(get_default_game_dir)
+    }
+
+    // 登记游戏目录，已存在时只更新名称
+    pub fn add_game_dir(&mut self, name: String, path: &Path) {
+        let path = to_stored_path(path);
+        match self.game_dirs.iter_mut().find(|dir| dir.path == path) {
+            Some(dir) => dir.name = name,
+            None => self.game_dirs.push(GameDirectory { name, path }),
+        }
+    }
+}
+
+// 启动器可执行文件所在目录
+fn get_exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+}
+
+// 可执行文件旁存在标记文件时启用便携模式
+pub fn is_portable() -> bool {
+    get_exe_dir().map_or(false, |dir| dir.join(PORTABLE_MARKER).is_file())
+}
+
+// 启动器自身的数据目录，存放设置等文件；便携模式下为可执行文件所在目录
+pub fn get_launcher_dir() -> PathBuf {
+    if let Some(exe_dir) = get_exe_dir().filter(|_| is_portable()) {
+        return exe_dir;
+    }
+
+    let home = home_dir().unwrap_or_default();
+    match OS {
+        "windows" => std::env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join("AppData").join("Roaming"))
+            .join("RTLauncher"),
+        "macos" => home
+            .join("Library")
+            .join("Application Support")
+            .join("RTLauncher"),
+        _ => std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".config"))
+            .join("rtlauncher"),
+    }
+}
+
+// 启动器日志目录
+pub fn get_logs_dir() -> PathBuf {
+    get_launcher_dir().join("logs")
+}
+
+// 启动器管理的Java运行时，每个运行时一个子目录
+pub fn get_java_runtimes_dir() -> PathBuf {
+    get_launcher_dir().join("runtimes")
+}
+
+// 保存路径时，便携模式下位于启动器目录内的路径改为相对路径
+pub fn to_stored_path(path: &Path) -> String {
+    if is_portable() {
+        if let Ok(relative) = path.strip_prefix(get_launcher_dir()) {
+            return relative.to_string_lossy().into_owned();
+        }
+    }
+    path.to_string_lossy().into_owned()
+}
+
+// 读取保存的路径，相对路径按启动器目录解析
+pub fn resolve_stored_path(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        get_launcher_dir().join(path)
+    }
+}
+
+// 各平台默认的游戏目录；便携模式下为启动器目录内的 .minecraft
+pub fn get_default_game_dir() -> PathBuf {
+    if is_portable() {
+        return get_launcher_dir().join(".minecraft");
+    }
+
+    let home = home_dir().unwrap_or_default();
+    match OS {
+        "windows" => std::env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join("AppData").join("Roaming"))
+            .join(".minecraft"),
+        "macos" => home
+            .join("Library")
+            .join("Application Support")
+            .join("minecraft"),
+        _ => std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local").join("share"))
+            .join("minecraft"),
+    }
+}
+
+fn game_directories(setting: &LauncherSetting) -> GameDirectories {
+    GameDirectories {
+        dirs: setting.game_dirs.clone(),
+        selected: setting.selected_path().cloned().unwrap_or_default(),
+    }
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn get_game_dirs() -> GameDirectories {
+    game_directories(&LauncherSetting::load())
+}
+
+#[tauri::command]
+pub fn add_game_dir(name: String, path: String) -> Result<GameDirectories, String> {
+    let path = PathBuf::from(path);
+    std::fs::create_dir_all(&path).map_err(|e| format!("创建游戏目录失败: {}", e))?;
+
+    let mut setting = LauncherSetting::load();
+    setting.add_game_dir(name, &path);
+    setting.save().map_err(|e| format!("保存设置失败: {}", e))?;
+    Ok(game_directories(&setting))
+}
+
+#[tauri::command]
+pub fn remove_game_dir(path: String) -> Result<GameDirectories, String> {
+    let mut setting = LauncherSetting::load();
+    if setting.game_dirs.len() <= 1 {
+        return Err("至少需要保留一个游戏目录".to_string());
+    }
+    setting.game_dirs.retain(|dir| dir.path != path);
+    if setting.selected_game_dir.as_deref() == Some(path.as_str()) {
+        setting.selected_game_dir = None;
+    }
+    setting.save().map_err(|e| format!("保存设置失败: {}", e))?;
+    Ok(game_directories(&setting))
+}
+
+#[tauri::command]
+pub fn select_game_dir(path: String) -> Result<GameDirectories, String> {
+    let mut setting = LauncherSetting::load();
+    if !setting.game_dirs.iter().any(|dir| dir.path == path) {
+        return Err(format!("游戏目录未登记: {}", path));
+    }
+    setting.selected_game_dir = Some(path);
+    setting.save().map_err(|e| format!("保存设置失败: {}", e))?;
+    Ok(game_directories(&setting))
+}
+
+// 设置是否在日志中隐藏uuid
+#[tauri::command]
+pub fn set_redact_uuid(enabled: bool) -> Result<(), String> {
+    let mut setting = LauncherSetting::load();
+    setting.redact_uuid = enabled;
+    setting.save().map_err(|e| format!("保存设置失败: {}", e))
+}
@@ -0,0 +1 @@
+pub mod launcher_setting;
@@ -1 +1,2 @@
-pub mod login;
\ No newline at end of file
+pub mod login;
+pub mod account;
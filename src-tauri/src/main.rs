@@ -11,20 +11,77 @@ mod router;
 mod utils;
 
 use api::login::get_code;
-use module::download::dwl_main::dwl_version_manifest;
+use module::crash::mappings::deobfuscate_log;
+use module::crash::attribution::attribute_crash;
+use module::download::dwl_main::{delete_version, dwl_version_manifest};
 use module::download::dwl_main::get_version_manifest;
-use module::start_game::stg_main::stg;
+use module::instance::import::import_minecraft_dir;
+use module::instance::instance::{
+    clone_instance, create_instance, delete_instance, list_instances, rename_instance, update_instance,
+};
+use module::instance::memory::get_memory_plan;
+use module::mods::bisect::{bisect_mods, cancel_bisect};
+use module::mods::metadata::list_mods;
+use module::instance::history::{get_account_playtime, get_instance_playtime, list_instance_playtime};
+use module::start_game::preflight::preflight_instance;
+use module::start_game::jvm_flags::{check_jvm_flags, list_jvm_presets};
+use module::start_game::process::{kill_game, list_running_games, wait_game};
+use module::start_game::quick_play::parse_launch_request;
+use module::start_game::stg_main::{launch_from_request, stg, stg_instance};
+use Setting::launcher_setting::{add_game_dir, get_game_dirs, remove_game_dir, select_game_dir, set_redact_uuid};
+use utils::create_shortcut::create_server_shortcut;
 use utils::export_bat::export_bat;
+use utils::get_java_path::get_java_installations;
 use utils::get_java_path::get_java_path;
+use utils::test_java::test_java;
 fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            // 由快捷方式启动时直接启动游戏
+            if let Some(request) = parse_launch_request(std::env::args()) {
+                launch_from_request(app.handle().clone(), request);
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_code,
             get_version_manifest,
             dwl_version_manifest,
+            delete_version,
             get_java_path,
+            get_java_installations,
+            test_java,
             stg,
-            export_bat
+            stg_instance,
+            preflight_instance,
+            list_jvm_presets,
+            check_jvm_flags,
+            list_running_games,
+            kill_game,
+            wait_game,
+            deobfuscate_log,
+            attribute_crash,
+            export_bat,
+            create_server_shortcut,
+            get_game_dirs,
+            add_game_dir,
+            remove_game_dir,
+            select_game_dir,
+            set_redact_uuid,
+            list_instances,
+            create_instance,
+            update_instance,
+            clone_instance,
+            rename_instance,
+            delete_instance,
+            import_minecraft_dir,
+            get_memory_plan,
+            get_instance_playtime,
+            list_instance_playtime,
+            get_account_playtime,
+            list_mods,
+            bisect_mods,
+            cancel_bisect
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
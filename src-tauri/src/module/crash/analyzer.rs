@@ -0,0 +1,281 @@
+// ***
+// 崩溃分析，收集崩溃报告与日志并匹配已知问题
+// ***
+
+use super::attribution::{ModIndex, ModSuspect};
+use super::mappings::Mappings;
+use crate::utils::redact::Redactor;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 分析时读取的游戏日志行数
+const LOG_TAIL_LINES: usize = 200;
+
+// 已知的崩溃原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    WrongJavaVersion,
+    MissingModDependency,
+    OutOfMemory,
+    GraphicsInit,
+    DuplicateMods,
+    MixinFailure,
+}
+
+// 一条诊断结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashDiagnosis {
+    pub kind: CrashKind,
+    pub title: String,
+    pub suggestion: String,
+    pub evidence: String, // 命中的原文行
+    pub source: String,   // 命中的文件
+}
+
+// 一次崩溃的分析结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    pub exit_code: Option<i32>,
+    pub crash_report: Option<PathBuf>,
+    pub crash_text: Option<String>, // 还原混淆名后的崩溃报告内容
+    pub hs_err: Option<PathBuf>,
+    pub log_tail: Vec<String>,
+    pub diagnoses: Vec<CrashDiagnosis>,
+    pub suspects: Vec<ModSuspect>, // 按堆栈推测的可能导致崩溃的模组
+}
+
+impl CrashReport {
+    // 崩溃报告与hs_err中可能带有完整启动命令
+    pub fn redact(&mut self, redactor: &Redactor) {
+        self.crash_text = self.crash_text.take().map(|text| redactor.redact(&text));
+        for line in &mut self.log_tail {
+            *line = redactor.redact(line);
+        }
+        for diagnosis in &mut self.diagnoses {
+            diagnosis.evidence = redactor.redact(&diagnosis.evidence);
+        }
+        for suspect in &mut self.suspects {
+            for line in &mut suspect.evidence {
+                *line = redactor.redact(line);
+            }
+        }
+    }
+}
+
+struct Rule {
+    kind: CrashKind,
+    patterns: &'static [&'static str], // 小写，按子串匹配
+    title: &'static str,
+    suggestion: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        kind: CrashKind::WrongJavaVersion,
+        patterns: &[
+            "unsupportedclassversionerror",
+            "compiled by a more recent version of the java runtime",
+            "unsupported class file major version",
+            "cannot be cast to class java.net.urlclassloader",
+            "sun.misc.unsafe.defineclass",
+        ],
+        title: "Java版本不匹配",
+        suggestion: "当前Java版本与游戏或加载器不兼容，请在实例设置中选择版本要求的Java",
+    },
+    Rule {
+        kind: CrashKind::MissingModDependency,
+        patterns: &[
+            "missing or unsupported mandatory dependencies",
+            "which is missing!",
+            "missingmodsexception",
+            "mod resolution failed",
+        ],
+        title: "缺少模组前置",
+        suggestion: "有模组缺少依赖或依赖版本不符，请根据日志安装对应的前置模组",
+    },
+    Rule {
+        kind: CrashKind::OutOfMemory,
+        patterns: &[
+            "java.lang.outofmemoryerror",
+            "could not reserve enough space for object heap",
+            "insufficient memory for the java runtime environment",
+        ],
+        title: "内存不足",
+        suggestion: "游戏内存不足，请调大最大内存或关闭其他占用内存的程序；32位Java最多只能使用约1.5G内存",
+    },
+    Rule {
+        kind: CrashKind::GraphicsInit,
+        patterns: &[
+            "glfw error 65542",
+            "glfw error 65543",
+            "the driver does not appear to support opengl",
+            "pixel format not accelerated",
+            "couldn't set pixel format",
+            "no opengl context found in the current thread",
+        ],
+        title: "显卡驱动或OpenGL初始化失败",
+        suggestion: "请更新显卡驱动，笔记本请确认游戏使用的是独立显卡",
+    },
+    Rule {
+        kind: CrashKind::DuplicateMods,
+        patterns: &[
+            "duplicatemodsfoundexception",
+            "found duplicate mods",
+            "duplicate mods found",
+            "is provided by multiple mods",
+        ],
+        title: "模组重复",
+        suggestion: "mods文件夹中存在同一模组的多个版本，请删除多余的文件",
+    },
+    Rule {
+        kind: CrashKind::MixinFailure,
+        patterns: &[
+            "mixin apply failed",
+            "mixinapplyerror",
+            "invalidinjectionexception",
+            "mixin transformation of",
+        ],
+        title: "Mixin注入失败",
+        suggestion: "有模组与当前游戏版本或其他模组不兼容，请根据日志中的模组名称更新或移除",
+    },
+];
+
+// 游戏非正常退出后收集相关文件并分析，只读取本次启动之后产生的崩溃报告
+pub fn analyze_crash(
+    game_dir: &Path,
+    log_file: &Path,
+    pid: u32,
+    started_at: u64,
+    exit_code: Option<i32>,
+    mappings: Option<&Mappings>,
+) -> CrashReport {
+    let deobfuscate = |text: String| match mappings {
+        Some(mappings) => mappings.deobfuscate(&text),
+        None => text,
+    };
+    let since = UNIX_EPOCH + Duration::from_secs(started_at);
+    let crash_report = newest_file(&game_dir.join("crash-reports"), since, |name| name.ends_with(".txt"));
+    let hs_err = Some(game_dir.join(format!("hs_err_pid{}.log", pid)))
+        .filter(|path| path.is_file())
+        .or_else(|| newest_file(game_dir, since, |name| name.starts_with("hs_err_pid")));
+    let log_tail = std::fs::read_to_string(log_file)
+        .map(|content| tail_lines(&deobfuscate(tail_lines(&content, LOG_TAIL_LINES).join("\n")), LOG_TAIL_LINES))
+        .unwrap_or_default();
+    let read_text = |path: &PathBuf| std::fs::read(path).ok().map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    let crash_text = crash_report.as_ref().and_then(read_text).map(deobfuscate);
+
+    let mut sources: Vec<(String, String)> = Vec::new();
+    if let (Some(path), Some(text)) = (&crash_report, &crash_text) {
+        sources.push((path.to_string_lossy().into_owned(), text.clone()));
+    }
+    if let Some(path) = &hs_err {
+        if let Some(text) = read_text(path) {
+            sources.push((path.to_string_lossy().into_owned(), text));
+        }
+    }
+    sources.push((log_file.to_string_lossy().into_owned(), log_tail.join("\n")));
+
+    // 有崩溃报告时按崩溃报告的堆栈推测，否则使用日志
+    let index = ModIndex::build(&game_dir.join("mods"));
+    let suspects = if index.is_empty() {
+        Vec::new()
+    } else {
+        index.attribute(crash_text.as_deref().unwrap_or(&log_tail.join("\n")))
+    };
+
+    CrashReport {
+        exit_code,
+        crash_report,
+        crash_text,
+        hs_err,
+        log_tail,
+        diagnoses: diagnose(&sources),
+        suspects,
+    }
+}
+
+// 按规则表匹配，每种原因只保留第一次命中
+fn diagnose(sources: &[(String, String)]) -> Vec<CrashDiagnosis> {
+    let mut diagnoses: Vec<CrashDiagnosis> = Vec::new();
+    for (source, content) in sources {
+        for line in content.lines() {
+            let lower = line.to_lowercase();
+            for rule in RULES {
+                if diagnoses.iter().any(|diagnosis| diagnosis.kind == rule.kind) {
+                    continue;
+                }
+                if rule.patterns.iter().any(|pattern| lower.contains(pattern)) {
+                    diagnoses.push(CrashDiagnosis {
+                        kind: rule.kind,
+                        title: rule.title.to_string(),
+                        suggestion: rule.suggestion.to_string(),
+                        evidence: line.trim().to_string(),
+                        source: source.clone(),
+                    });
+                }
+            }
+        }
+    }
+    diagnoses
+}
+
+// 目录中修改时间不早于since的最新文件
+fn newest_file(dir: &Path, since: SystemTime, filter: impl Fn(&str) -> bool) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| filter(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .filter(|(modified, _)| *modified >= since)
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+fn tail_lines(content: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<CrashKind> {
+        diagnose(&[("latest.log".to_string(), content.to_string())])
+            .iter()
+            .map(|diagnosis| diagnosis.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_diagnose() {
+        assert_eq!(
+            kinds("java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been compiled by a more recent version of the Java Runtime (class file version 65.0)"),
+            vec![CrashKind::WrongJavaVersion]
+        );
+        assert_eq!(
+            kinds("Mod 'Sodium Extra' (sodium-extra) 0.5.1 requires any version of sodium, which is missing!"),
+            vec![CrashKind::MissingModDependency]
+        );
+        assert_eq!(
+            kinds("[12:00:00] [Render thread/ERROR]: GLFW error 65542: WGL: The driver does not appear to support OpenGL\njava.lang.OutOfMemoryError: Java heap space"),
+            vec![CrashKind::GraphicsInit, CrashKind::OutOfMemory]
+        );
+        assert_eq!(
+            kinds("org.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError: An unexpected critical error was encountered\nCaused by: org.spongepowered.asm.mixin.injection.throwables.InvalidInjectionException: Critical injection failure"),
+            vec![CrashKind::MixinFailure]
+        );
+        assert!(kinds("[12:00:00] [main/INFO]: Stopping!").is_empty());
+    }
+
+    #[test]
+    fn test_tail_lines() {
+        assert_eq!(tail_lines("a\nb\nc", 2), vec!["b", "c"]);
+        assert_eq!(tail_lines("a", 5), vec!["a"]);
+    }
+}
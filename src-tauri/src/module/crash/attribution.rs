@@ -0,0 +1,313 @@
+// ***
+// 把崩溃堆栈对应到模组：按模组jar中的包名、mixin配置与模组id匹配堆栈中的帧
+// ***
+
+use crate::module::instance::instance::Instance;
+use crate::module::mods::metadata::{read_mod, ModInfo};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+// 最多返回的模组数量
+const MAX_SUSPECTS: usize = 5;
+// 每个模组保留的证据行数
+const MAX_EVIDENCE: usize = 3;
+// 每段异常中前几帧最接近出错位置，权重更高
+const TOP_FRAMES: usize = 5;
+// 原版、运行库与模组常打包的第三方库，不参与按包匹配
+const IGNORED_PACKAGES: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "net.minecraft.",
+    "com.mojang.",
+    "org.lwjgl.",
+    "com.google.",
+    "org.apache.",
+    "io.netty.",
+    "kotlin.",
+    "kotlinx.",
+    "it.unimi.",
+    "org.slf4j.",
+    "org.jetbrains.",
+    "org.objectweb.asm.",
+    "org.spongepowered.",
+    "com.llamalad7.mixinextras.",
+    "com.electronwill.nightconfig.",
+    "net.fabricmc.loader.",
+    "cpw.mods.",
+];
+// mixin生成的方法名前缀
+const MIXIN_PREFIXES: [&str; 6] = ["handler", "redirect", "modify", "wrapOperation", "localvar", "inject"];
+// 证据分数达到该值时可能性约为63%，分数越高越接近1
+const CONFIDENCE_SCALE: f64 = 2.5;
+
+// 可能导致崩溃的模组
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModSuspect {
+    pub file_name: String,
+    pub name: String,
+    pub mod_id: Option<String>,
+    pub confidence: f64, // 0~1，只由该模组自身证据的多少与强弱决定
+    pub evidence: Vec<String>,
+}
+
+// 一个模组jar的索引
+#[derive(Debug, Clone, Default)]
+struct IndexedMod {
+    info: ModInfo,
+    packages: Vec<String>,
+    mixin_configs: Vec<String>, // 文件名，如 sodium.mixins.json
+}
+
+// mods文件夹的索引
+#[derive(Debug, Default)]
+pub struct ModIndex {
+    mods: Vec<IndexedMod>,
+    packages: HashMap<String, Vec<usize>>, // 包名 -> 包含该包的模组，多个模组含有同一个包时通常是打包的库
+}
+
+impl ModIndex {
+    // 读取mods文件夹中启用的jar
+    pub fn build(mods_dir: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(mods_dir) else {
+            return Self::default();
+        };
+        let mods = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().to_lowercase().ends_with(".jar"))
+            .filter_map(|entry| index_jar(&entry.path()))
+            .collect();
+        Self::from_mods(mods)
+    }
+
+    fn from_mods(mods: Vec<IndexedMod>) -> Self {
+        let mut packages: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, indexed) in mods.iter().enumerate() {
+            for package in &indexed.packages {
+                packages.entry(package.clone()).or_default().push(index);
+            }
+        }
+        Self { mods, packages }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mods.is_empty()
+    }
+
+    // 提供某个id的模组
+    fn find_id(&self, id: &str) -> Option<usize> {
+        self.mods.iter().position(|indexed| indexed.info.provides(id))
+    }
+
+    // 匹配崩溃报告或日志，按可能性从高到低返回
+    pub fn attribute(&self, text: &str) -> Vec<ModSuspect> {
+        let mut scores: Vec<(f64, Vec<String>)> = vec![(0.0, Vec::new()); self.mods.len()];
+        let mut add = |index: usize, weight: f64, line: &str| {
+            let (score, evidence) = &mut scores[index];
+            *score += weight;
+            let line = line.trim().to_string();
+            if evidence.len() < MAX_EVIDENCE && !evidence.contains(&line) {
+                evidence.push(line);
+            }
+        };
+
+        let mut frame_position = 0;
+        for line in text.lines() {
+            let trimmed = line.trim();
+
+            // 新的一段异常
+            let is_exception = trimmed.starts_with("Caused by:") || trimmed.contains("Exception") || trimmed.contains("Error:");
+            if is_exception && !trimmed.starts_with("at ") {
+                frame_position = 0;
+            }
+
+            // 崩溃报告的 Mixins in Stacktrace 段，如 sodium.mixins.json:MixinFoo from mod sodium
+            if let Some((_, id)) = trimmed.split_once(" from mod ") {
+                if let Some(index) = self.find_id(id.trim()) {
+                    add(index, 2.0, line);
+                    continue;
+                }
+            }
+            if let Some(index) = self.mods.iter().position(|indexed| {
+                indexed.mixin_configs.iter().any(|config| trimmed.contains(&format!("{}:", config)))
+            }) {
+                add(index, 2.0, line);
+                continue;
+            }
+
+            let Some(frame) = trimmed.strip_prefix("at ") else {
+                continue;
+            };
+            frame_position += 1;
+            let weight = if frame_position <= TOP_FRAMES { 1.0 } else { 0.5 };
+            let frame = frame.split('(').next().unwrap_or(frame);
+
+            // Forge的模块名，如 TRANSFORMER/create@0.5.1/com.simibubi...
+            let (module, method) = match frame.rsplit_once('/') {
+                Some((module, method)) => (Some(module), method),
+                None => (None, frame),
+            };
+            if let Some(id) = module.and_then(|module| module.rsplit('/').next()?.split_once('@')).map(|(id, _)| id) {
+                if let Some(index) = self.find_id(id) {
+                    add(index, 1.5 * weight, line);
+                    continue;
+                }
+            }
+
+            // mixin注入的方法名第三段为模组id，如 handler$zza000$sodium$onRender
+            // lambda$create$0、access$000 等编译器生成的方法名不参与匹配
+            let Some((class, method_name)) = method.rsplit_once('.') else {
+                continue;
+            };
+            let mut segments = method_name.split('$');
+            let is_mixin = segments.next().map_or(false, |prefix| MIXIN_PREFIXES.contains(&prefix));
+            if let Some(index) = segments.nth(1).filter(|_| is_mixin).and_then(|id| self.find_id(id)) {
+                add(index, 1.5 * weight, line);
+                continue;
+            }
+
+            // 按包名匹配，多个模组含有同一个包时是打包的库，不作为证据
+            let package = class.rsplit_once('.').map(|(package, _)| package).unwrap_or_default();
+            if IGNORED_PACKAGES.iter().any(|ignored| class.starts_with(ignored)) {
+                continue;
+            }
+            if let Some([owner]) = self.packages.get(package).map(|owners| owners.as_slice()) {
+                add(*owner, weight, line);
+            }
+        }
+
+        let mut suspects: Vec<ModSuspect> = scores
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (score, _))| *score > 0.0)
+            .map(|(index, (score, evidence))| {
+                let info = &self.mods[index].info;
+                ModSuspect {
+                    file_name: info.file_name.clone(),
+                    name: info.name.clone(),
+                    mod_id: info.ids.first().cloned(),
+                    confidence: ((1.0 - (-score / CONFIDENCE_SCALE).exp()) * 100.0).round() / 100.0,
+                    evidence,
+                }
+            })
+            .collect();
+        suspects.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        suspects.truncate(MAX_SUSPECTS);
+        suspects
+    }
+}
+
+// 读取jar中的包名与mixin配置
+fn index_jar(path: &Path) -> Option<IndexedMod> {
+    let mut info = read_mod(path).ok()?;
+    info.file_name = path.file_name()?.to_string_lossy().into_owned();
+    if info.name.is_empty() {
+        info.name = info.file_name.trim_end_matches(".jar").to_string();
+    }
+
+    let mut archive = ZipArchive::new(File::open(path).ok()?).ok()?;
+    let names: Vec<String> = archive.file_names().map(|name| name.to_string()).collect();
+    let mut packages: Vec<String> = names
+        .iter()
+        .filter(|name| name.ends_with(".class") && !name.starts_with("META-INF/"))
+        .filter_map(|name| name.rsplit_once('/').map(|(dir, _)| dir.replace('/', ".")))
+        .collect();
+    packages.sort();
+    packages.dedup();
+
+    // 根目录中名称带mixin的json，读取其中的mixin包
+    let mut mixin_configs = Vec::new();
+    for name in names.iter().filter(|name| !name.contains('/') && name.ends_with(".json") && name.contains("mixin")) {
+        let Ok(mut file) = archive.by_name(name) else {
+            continue;
+        };
+        let mut content = String::new();
+        if file.read_to_string(&mut content).is_err() {
+            continue;
+        }
+        let config: Value = serde_json::from_str(&content).unwrap_or_default();
+        if let Some(package) = config["package"].as_str() {
+            if !packages.iter().any(|own| own == package) {
+                packages.push(package.to_string());
+            }
+        }
+        mixin_configs.push(name.clone());
+    }
+
+    Some(IndexedMod {
+        info,
+        packages,
+        mixin_configs,
+    })
+}
+
+// 共享方法到前端，text 为崩溃报告或日志内容
+#[tauri::command]
+pub async fn attribute_crash(instance_id: String, text: String) -> Result<Vec<ModSuspect>, String> {
+    let instance = Instance::load(&instance_id)?;
+    tokio::task::spawn_blocking(move || ModIndex::build(&instance.game_dir().join("mods")).attribute(&text))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed(file_name: &str, id: &str, packages: &[&str], mixin_configs: &[&str]) -> IndexedMod {
+        IndexedMod {
+            info: ModInfo {
+                file_name: file_name.to_string(),
+                ids: vec![id.to_string()],
+                name: id.to_string(),
+                ..ModInfo::default()
+            },
+            packages: packages.iter().map(|package| package.to_string()).collect(),
+            mixin_configs: mixin_configs.iter().map(|config| config.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_attribute() {
+        let index = ModIndex::from_mods(vec![
+            indexed("sodium.jar", "sodium", &["me.jellysquid.mods.sodium.client.render"], &["sodium.mixins.json"]),
+            indexed("create.jar", "create", &["com.simibubi.create.content", "com.google.gson", "shaded.lib"], &[]),
+            indexed("lib.jar", "somelib", &["com.google.gson", "shaded.lib"], &[]),
+        ]);
+        let report = "java.lang.NullPointerException: Cannot invoke \"Object.hashCode()\"
+\tat me.jellysquid.mods.sodium.client.render.ChunkRenderer.render(ChunkRenderer.java:42)
+\tat net.minecraft.class_761.handler$zza000$sodium$onRender(class_761.java:1)
+\tat net.minecraft.client.main.Main.main(Main.java:10)
+Mixins in Stacktrace:
+\tnet.minecraft.class_761:
+\t\tsodium.mixins.json:MixinWorldRenderer from mod sodium
+Caused by: java.lang.IllegalStateException
+\tat com.google.gson.Gson.fromJson(Gson.java:1)
+\tat shaded.lib.Util.run(Util.java:1)";
+
+        let suspects = index.attribute(report);
+        assert_eq!(suspects[0].file_name, "sodium.jar");
+        assert!(suspects[0].confidence > 0.8);
+        assert_eq!(suspects[0].evidence.len(), 3);
+        // gson与多个模组都含有的包不作为证据
+        assert_eq!(suspects.len(), 1);
+
+        // 原版中编译器生成的方法名不算作模组的证据
+        let lambda = "java.lang.NullPointerException
+\tat net.minecraft.class_1234.lambda$create$0(class_1234.java:1)
+\tat net.minecraft.class_1234.access$000(class_1234.java:1)";
+        assert!(index.attribute(lambda).is_empty());
+
+        // 只有一帧时可能性较低
+        let weak = index.attribute("java.lang.IllegalStateException\n\tat com.simibubi.create.content.Foo.bar(Foo.java:1)");
+        assert_eq!(weak[0].file_name, "create.jar");
+        assert!(weak[0].confidence < 0.5);
+        assert!(index.attribute("[main/INFO]: Stopping!").is_empty());
+    }
+}
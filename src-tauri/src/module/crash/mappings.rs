@@ -0,0 +1,189 @@
+// ***
+// ProGuard混淆表，将崩溃报告中的混淆名还原为官方名称
+// ***
+
+use crate::module::download::dwl_main::download_server_mappings;
+use crate::module::download::paths::MinecraftPaths;
+use crate::module::start_game::version::resolve_version;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+struct ClassMapping {
+    name: String,
+    methods: HashMap<String, Vec<String>>, // 混淆名 -> 原名，重载可能对应多个
+}
+
+// 以混淆名为键的映射表
+#[derive(Debug, Default)]
+pub struct Mappings {
+    classes: HashMap<String, ClassMapping>,
+}
+
+impl Mappings {
+    // 格式：类为 `原名 -> 混淆名:`，成员缩进，方法为 `[行号:行号:]返回值 方法名(参数)[:行号:行号] -> 混淆名`
+    pub fn parse(content: &str) -> Self {
+        let mut classes: HashMap<String, ClassMapping> = HashMap::new();
+        let mut current: Option<String> = None;
+
+        for line in content.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let Some((original, obfuscated)) = line.trim().split_once(" -> ") else {
+                continue;
+            };
+
+            if !line.starts_with(' ') && !line.starts_with('\t') {
+                let obfuscated = obfuscated.trim_end_matches(':').to_string();
+                classes.insert(
+                    obfuscated.clone(),
+                    ClassMapping {
+                        name: original.to_string(),
+                        ..Default::default()
+                    },
+                );
+                current = Some(obfuscated);
+                continue;
+            }
+
+            // 只需要方法，字段没有括号
+            let Some(class) = current.as_ref().and_then(|class| classes.get_mut(class)) else {
+                continue;
+            };
+            let Some(paren) = original.find('(') else {
+                continue;
+            };
+            let Some(name) = original[..paren].rsplit(' ').next() else {
+                continue;
+            };
+            let names = class.methods.entry(obfuscated.to_string()).or_default();
+            if !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        }
+
+        Self { classes }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("读取混淆表 {} 失败: {}", path.display(), e))?;
+        Ok(Self::parse(&content))
+    }
+
+    // 读取版本对应的混淆表，加载器版本使用其客户端jar所属版本的混淆表
+    pub fn for_version(paths: &MinecraftPaths, version_id: &str, server: bool) -> Result<Self, String> {
+        let jar_id = resolve_version(paths, version_id)
+            .ok()
+            .and_then(|json| json["jar"].as_str().map(|jar| jar.to_string()))
+            .unwrap_or_else(|| version_id.to_string());
+        let path = paths.get_mappings_path(&jar_id, server);
+        if !path.is_file() {
+            return Err(format!("版本 {} 没有混淆表", jar_id));
+        }
+        Self::load(&path)
+    }
+
+    fn class_name(&self, obfuscated: &str) -> Option<&str> {
+        self.classes.get(obfuscated).map(|class| class.name.as_str())
+    }
+
+    // 还原文本中的混淆名
+    // 带点的名称按 类.方法 或 包.类 处理；单独的短名称容易与普通单词冲突，只在 class 之后或含 $ 时替换
+    pub fn deobfuscate(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut token = String::new();
+
+        let is_token_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.';
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if is_token_char(c) {
+                token.push(c);
+                if chars.peek().map_or(true, |next| !is_token_char(*next)) {
+                    let previous_word = result.trim_end_matches(' ').rsplit([' ', '\t', '\n']).next().unwrap_or("");
+                    let after_class = previous_word == "class" && result.ends_with(' ');
+                    result.push_str(&self.deobfuscate_token(&token, after_class));
+                    token.clear();
+                }
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    fn deobfuscate_token(&self, token: &str, after_class: bool) -> String {
+        let token_trimmed = token.trim_end_matches('.');
+        let suffix = &token[token_trimmed.len()..];
+
+        if let Some((owner, member)) = token_trimmed.rsplit_once('.') {
+            if let Some(class) = self.classes.get(owner) {
+                let method = class
+                    .methods
+                    .get(member)
+                    .map(|names| names.join("/"))
+                    .unwrap_or_else(|| member.to_string());
+                return format!("{}.{}{}", class.name, method, suffix);
+            }
+            if let Some(name) = self.class_name(token_trimmed) {
+                return format!("{}{}", name, suffix);
+            }
+            return token.to_string();
+        }
+
+        if after_class || token_trimmed.contains('$') {
+            if let Some(name) = self.class_name(token_trimmed) {
+                return format!("{}{}", name, suffix);
+            }
+        }
+        token.to_string()
+    }
+}
+
+// 共享方法到前端，server为true时使用服务端混淆表
+#[tauri::command]
+pub async fn deobfuscate_log(version_id: String, text: String, server: Option<bool>) -> Result<String, String> {
+    let paths = MinecraftPaths::new();
+    let server = server.unwrap_or(false);
+    if server {
+        download_server_mappings(&paths, &version_id).await?;
+    }
+    let mappings = Mappings::for_version(&paths, &version_id, server)?;
+    Ok(mappings.deobfuscate(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPINGS: &str = "# {\"id\":\"sourceFile\"}
+net.minecraft.client.Minecraft -> fgo:
+# {\"fileName\":\"Minecraft.java\",\"id\":\"sourceFile\"}
+    int fps -> a
+    1:5:void run() -> f
+    10:12:void run(boolean):100:102 -> f
+    20:21:boolean isDemo() -> g
+net.minecraft.client.Minecraft$GameLoadCookie -> fgo$a:
+    void <init>() -> <init>
+";
+
+    #[test]
+    fn test_parse_mappings() {
+        let mappings = Mappings::parse(MAPPINGS);
+        assert_eq!(mappings.class_name("fgo"), Some("net.minecraft.client.Minecraft"));
+        assert_eq!(mappings.classes["fgo"].methods["f"], vec!["run"]);
+        assert!(!mappings.classes["fgo"].methods.contains_key("a"));
+    }
+
+    #[test]
+    fn test_deobfuscate() {
+        let mappings = Mappings::parse(MAPPINGS);
+        let text = "java.lang.ClassCastException: class fgo cannot be cast to class fgo$a\n\tat fgo.g(SourceFile:20)\n\tat fgo$a.<init>(SourceFile:3)";
+        assert_eq!(
+            mappings.deobfuscate(text),
+            "java.lang.ClassCastException: class net.minecraft.client.Minecraft cannot be cast to class net.minecraft.client.Minecraft$GameLoadCookie\n\tat net.minecraft.client.Minecraft.isDemo(SourceFile:20)\n\tat net.minecraft.client.Minecraft$GameLoadCookie.<init>(SourceFile:3)"
+        );
+        // 普通单词不替换
+        assert_eq!(mappings.deobfuscate("fgo is fine"), "fgo is fine");
+    }
+}
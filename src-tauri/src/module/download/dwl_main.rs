@@ -13,7 +13,9 @@ use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use super::get_user_os;
 use super::decompression::decompression;
-use walkdir;
+use super::paths::MinecraftPaths;
+use crate::module::start_game::process::lock_version_install;
+use crate::module::start_game::version::resolve_version;
 
 pub struct Download {
     pub version_manifest_url: String, // 获取版本url
@@ -69,69 +71,6 @@ impl DownloadProgress {
     }
 }
 
-// 路径管理
-pub struct MinecraftPaths {
-    pub base_dir: std::path::PathBuf,
-    pub versions_dir: std::path::PathBuf,
-    pub libraries_dir: std::path::PathBuf,
-    pub assets_dir: std::path::PathBuf,
-}
-
-
-impl MinecraftPaths {
-    pub fn new() -> Self {
-        // 游戏文件保存路径
-        let base_dir = std::path::PathBuf::from("D:\\Desktop\\.minecraft");
-        Self {
-            versions_dir: base_dir.join("version"),
-            libraries_dir: base_dir.join("libraries"),
-            assets_dir: base_dir.join("assets"),
-            base_dir,
-        }
-    }
-
-    pub fn get_version_dir(&self, version_id: &str) -> std::path::PathBuf {
-        self.versions_dir.join(version_id)
-    }
-
-    pub fn get_natives_dir(&self, version_id: &str) -> std::path::PathBuf {
-        self.get_version_dir(version_id).join(format!("{}-natives", version_id))
-    }
-
-    pub fn ensure_dirs(&self) -> std::io::Result<()> {
-        std::fs::create_dir_all(&self.base_dir)?;
-        std::fs::create_dir_all(&self.versions_dir)?;
-        std::fs::create_dir_all(&self.libraries_dir)?;
-        std::fs::create_dir_all(&self.assets_dir)?;
-        Ok(())
-    }
-
-    // 获取绝对路径-公共方法
-    pub fn get_absolute_path(&self, path: std::path::PathBuf) -> String {
-        path.canonicalize()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string()
-            .trim_start_matches(r"\\?\")
-
-            .to_string()
-    }
-
-    // 获取libraries目录下所有jar文件的路径
-    pub fn get_libraries_classpath(&self) -> Vec<String> {
-        walkdir::WalkDir::new(&self.libraries_dir)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry.path()
-                    .extension()
-                    .map_or(false, |ext| ext == "jar")
-            })
-            .map(|entry| self.get_absolute_path(entry.path().to_path_buf()))
-            .collect()
-    }
-}
-
 #[tauri::command]
 pub async fn get_version_manifest() -> Result<serde_json::Value, String> {
     let download = Download::new(String::from(
@@ -169,6 +108,54 @@ pub async fn dwl_version_manifest(url: String) -> Result<serde_json::Value, Stri
     Ok(json_value)
 }
 
+// 服务端混淆表只在安装或启动服务端、还原服务端日志时下载，已存在时跳过
+pub async fn download_server_mappings(paths: &MinecraftPaths, version_id: &str) -> Result<(), String> {
+    let json_value = resolve_version(paths, version_id)?;
+    let jar_id = json_value["jar"].as_str().unwrap_or(version_id);
+    let mapping_path = paths.get_mappings_path(jar_id, true);
+    if mapping_path.is_file() {
+        return Ok(());
+    }
+    let Some(mapping_url) = json_value["downloads"]["server_mappings"]["url"].as_str() else {
+        return Ok(());
+    };
+    let info = download_file(mapping_url.to_string(), mapping_path)
+        .await
+        .map_err(|e| format!("服务端映射文件下载失败: {}", e))?;
+    println!("✅ 服务端映射文件下载成功: {} -> {}", info.url, info.path.display());
+    Ok(())
+}
+
+// 删除已安装的版本，游戏正在使用或有其他版本继承它时不允许删除
+#[tauri::command]
+pub fn delete_version(version_id: String) -> Result<(), String> {
+    let valid = !version_id.is_empty()
+        && std::path::Path::new(&version_id).file_name().map_or(false, |name| name == version_id.as_str());
+    if !valid {
+        return Err(format!("无效的版本id: {}", version_id));
+    }
+    let paths = MinecraftPaths::new();
+    let _install = lock_version_install(&paths.versions_dir, &version_id)?;
+    let version_dir = paths.get_version_dir(&version_id);
+    if !version_dir.is_dir() {
+        return Err(format!("版本 {} 不存在", version_id));
+    }
+    let children: Vec<String> = std::fs::read_dir(&paths.versions_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|id| {
+            paths
+                .read_version_json(id)
+                .map_or(false, |json| json["inheritsFrom"].as_str() == Some(version_id.as_str()))
+        })
+        .collect();
+    if !children.is_empty() {
+        return Err(format!("版本 {} 被 {} 继承，请先删除这些版本", version_id, children.join("、")));
+    }
+    std::fs::remove_dir_all(&version_dir).map_err(|e| format!("删除版本失败: {}", e))
+}
+
 impl Download {
     pub fn new(version_manifest_url: String) -> Self {
         Self {
@@ -207,7 +194,10 @@ impl DownloadOptions {
         // 解析json
         let json_value: serde_json::Value = serde_json::from_str(&res)?;
         let version_id = json_value["id"].as_str().unwrap_or("unknown");
-        
+        // 游戏运行时重新安装会覆盖正在使用的文件，安装期间也不允许启动该版本
+        let paths = MinecraftPaths::new();
+        let _install = lock_version_install(&paths.versions_dir, version_id)?;
+
         // 获取asset_index_id
         let asset_index_id = json_value
             .get("assetIndex")
@@ -216,13 +206,15 @@ impl DownloadOptions {
             .unwrap_or("unknown")
             .to_string();
 
-        let paths = MinecraftPaths::new();
         paths.ensure_dirs()?;
 
 
         let version_path = paths.get_version_dir(version_id);
         std::fs::create_dir_all(&version_path)?;
 
+        // 保存版本json，启动时读取
+        std::fs::write(paths.get_version_json_path(version_id), &res)?;
+
         let mut success_count = 0;
         let mut failed_count = 0;
         let current_os = get_user_os(); // 获取当前操作系统
@@ -260,8 +252,12 @@ impl DownloadOptions {
         if let Some(logging) = json_value.get("logging") {
             if let Some(client) = logging.get("client") {
                 if let Some(file) = client.get("file") {
-                    if let Some(xml_url) = file.get("url").and_then(|u| u.as_str()) {
-                        let xml_path = version_path.join("client-1.12.xml");
+                    let xml_url = file.get("url").and_then(|u| u.as_str());
+                    let xml_path = file
+                        .get("id")
+                        .and_then(|id| id.as_str())
+                        .and_then(|id| paths.get_log_config_path(version_id, id));
+                    if let (Some(xml_url), Some(xml_path)) = (xml_url, xml_path) {
                         match download_file(xml_url.to_string(), xml_path).await {
                             Ok(info) => {
                                 println!("✅ 日志配置文件下载成功: {} -> {}", info.url, info.path.display());
@@ -606,26 +602,25 @@ impl DownloadOptions {
         // 添加耗时统计
         timings.push(("Libraries".to_string(), libs_duration));
 
-        // 3. 客户端映射文件 - 直接存储在版本目录中
-        if let Some(client_mappings) = json_value
+        // 3. 客户端映射文件 - 直接存储在版本目录中，服务端映射文件在需要时下载
+        if let Some(mapping_url) = json_value
             .get("downloads")
             .and_then(|downloads| downloads.get("client_mappings"))
+            .and_then(|mappings| mappings["url"].as_str())
         {
-            if let Some(mapping_url) = client_mappings["url"].as_str() {
-                let mapping_path = version_path.join(format!("{}-mappings.txt", version_id));
-                match download_file(mapping_url.to_string(), mapping_path).await {
-                    Ok(info) => {
-                        println!(
-                            "✅ 映射文件下载成功: {} -> {}",
-                            info.url,
-                            info.path.display()
-                        );
-                        success_count += 1;
-                    }
-                    Err(e) => {
-                        println!("❌ 映射文件下载失败: {}", e);
-                        failed_count += 1;
-                    }
+            let mapping_path = paths.get_mappings_path(version_id, false);
+            match download_file(mapping_url.to_string(), mapping_path).await {
+                Ok(info) => {
+                    println!(
+                        "✅ 映射文件下载成功: {} -> {}",
+                        info.url,
+                        info.path.display()
+                    );
+                    success_count += 1;
+                }
+                Err(e) => {
+                    println!("❌ 映射文件下载失败: {}", e);
+                    failed_count += 1;
                 }
             }
         }
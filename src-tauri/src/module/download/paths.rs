@@ -2,6 +2,7 @@
 // 路径配置管理模块
 // ***
 
+use crate::Setting::launcher_setting::LauncherSetting;
 use std::path::PathBuf;
 
 pub struct MinecraftPaths {
@@ -9,15 +10,21 @@ pub struct MinecraftPaths {
     pub versions_dir: PathBuf,
     pub libraries_dir: PathBuf,
     pub assets_dir: PathBuf,
+    pub instances_dir: PathBuf,
 }
 
 impl MinecraftPaths {
+    // 使用设置中选中的游戏目录
     pub fn new() -> Self {
-        let base_dir = PathBuf::from("D:\\Desktop\\.minecraft");
+        Self::from_base_dir(LauncherSetting::load().current_game_dir())
+    }
+
+    pub fn from_base_dir(base_dir: PathBuf) -> Self {
         Self {
-            versions_dir: base_dir.join("version"),
+            versions_dir: base_dir.join("versions"),
             libraries_dir: base_dir.join("libraries"),
             assets_dir: base_dir.join("assets"),
+            instances_dir: base_dir.join("instances"),
             base_dir: base_dir,
         }
     }
@@ -26,6 +33,35 @@ impl MinecraftPaths {
         self.versions_dir.join(version_id)
     }
 
+    // 版本json路径
+    pub fn get_version_json_path(&self, version_id: &str) -> PathBuf {
+        self.get_version_dir(version_id).join(format!("{}.json", version_id))
+    }
+
+    // 读取已安装版本的json
+    pub fn read_version_json(&self, version_id: &str) -> Result<serde_json::Value, String> {
+        let content = std::fs::read_to_string(self.get_version_json_path(version_id))
+            .map_err(|e| format!("读取版本 {} 的json失败: {}", version_id, e))?;
+        serde_json::from_str(&content).map_err(|e| format!("版本json解析错误: {}", e))
+    }
+
+    // 官方混淆表，server为true时为服务端的混淆表
+    pub fn get_mappings_path(&self, version_id: &str, server: bool) -> PathBuf {
+        let file_name = if server {
+            format!("{}-server-mappings.txt", version_id)
+        } else {
+            format!("{}-mappings.txt", version_id)
+        };
+        self.get_version_dir(version_id).join(file_name)
+    }
+
+    // 日志配置文件，按版本json中 logging.client.file.id 保存，如 client-1.7.xml、client-1.12.xml
+    // id不是单独的文件名时返回None
+    pub fn get_log_config_path(&self, version_id: &str, file_id: &str) -> Option<PathBuf> {
+        let valid = std::path::Path::new(file_id).file_name().map_or(false, |name| name == file_id);
+        valid.then(|| self.get_version_dir(version_id).join(file_id))
+    }
+
     pub fn get_natives_dir(&self, version_id: &str) -> PathBuf {
         self.get_version_dir(version_id).join(format!("{}-natives", version_id))
     }
@@ -42,20 +78,10 @@ impl MinecraftPaths {
     #[allow(dead_code)]
     pub fn get_absolute_path(&self, path: PathBuf) -> String {
         path.canonicalize()
-            .unwrap_or_default()
+            .unwrap_or(path)
             .to_string_lossy()
             .to_string()
             .trim_start_matches(r"\\?\")
             .to_string()
     }
-
-    #[allow(dead_code)]
-    pub fn get_libraries_classpath(&self) -> Vec<String> {
-        walkdir::WalkDir::new(&self.libraries_dir)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "jar"))
-            .map(|entry| self.get_absolute_path(entry.path().to_path_buf()))
-            .collect()
-    }
 }
\ No newline at end of file
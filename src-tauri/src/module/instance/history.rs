@@ -0,0 +1,184 @@
+// ***
+// 实例的游戏时长与启动记录
+// ***
+
+use super::instance::Instance;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::path::Path;
+use std::sync::Mutex;
+
+pub const HISTORY_FILE: &str = "history.json";
+// 默认返回的最近记录数量
+const RECENT_SESSIONS: usize = 20;
+
+// 多个游戏同时退出时避免同时改写记录文件
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+// 一次游戏的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub started_at: u64, // unix时间戳，秒
+    pub ended_at: u64,
+    pub exit_code: Option<i32>,
+    pub username: String,
+    pub java_path: String,
+    pub crashed: bool,
+}
+
+impl SessionRecord {
+    pub fn duration_secs(&self) -> u64 {
+        self.ended_at.saturating_sub(self.started_at)
+    }
+}
+
+// 单个账号的游戏时长
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountPlaytime {
+    pub username: String,
+    pub total_secs: u64,
+    pub session_count: usize,
+    pub last_played: Option<u64>,
+}
+
+// 实例的游戏时长统计，返回给前端
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaytimeSummary {
+    pub instance_id: String,
+    pub total_secs: u64,
+    pub session_count: usize,
+    pub crash_count: usize,
+    pub last_played: Option<u64>,
+    pub accounts: Vec<AccountPlaytime>,
+    pub recent: Vec<SessionRecord>, // 最近的记录在前
+}
+
+// 读取实例目录中的记录，文件不存在时为空
+pub fn load_history(instance_dir: &Path) -> Vec<SessionRecord> {
+    std::fs::read_to_string(instance_dir.join(HISTORY_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// 游戏退出时由进程管理调用，instance_dir 为启动时的实例目录，运行期间切换游戏目录不影响记录
+pub fn record_session(instance_dir: &Path, record: SessionRecord) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().unwrap();
+    let mut history = load_history(instance_dir);
+    history.push(record);
+    let content = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+    std::fs::write(instance_dir.join(HISTORY_FILE), content).map_err(|e| format!("保存游戏记录失败: {}", e))
+}
+
+// 按记录统计时长，recent 只保留最近 limit 条
+pub fn summarize(instance_id: &str, history: &[SessionRecord], limit: usize) -> PlaytimeSummary {
+    let mut accounts: Vec<AccountPlaytime> = Vec::new();
+    for record in history {
+        let index = match accounts.iter().position(|account| account.username == record.username) {
+            Some(index) => index,
+            None => {
+                accounts.push(AccountPlaytime {
+                    username: record.username.clone(),
+                    total_secs: 0,
+                    session_count: 0,
+                    last_played: None,
+                });
+                accounts.len() - 1
+            }
+        };
+        let account = &mut accounts[index];
+        account.total_secs += record.duration_secs();
+        account.session_count += 1;
+        account.last_played = account.last_played.max(Some(record.ended_at));
+    }
+    accounts.sort_by_key(|account| Reverse(account.total_secs));
+
+    let mut recent = history.to_vec();
+    recent.sort_by_key(|record| Reverse(record.started_at));
+    recent.truncate(limit);
+
+    PlaytimeSummary {
+        instance_id: instance_id.to_string(),
+        total_secs: history.iter().map(|record| record.duration_secs()).sum(),
+        session_count: history.len(),
+        crash_count: history.iter().filter(|record| record.crashed).count(),
+        last_played: history.iter().map(|record| record.ended_at).max(),
+        accounts,
+        recent,
+    }
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn get_instance_playtime(instance_id: String, limit: Option<usize>) -> Result<PlaytimeSummary, String> {
+    let instance = Instance::load(&instance_id)?;
+    let history = load_history(&instance.dir);
+    Ok(summarize(&instance.id, &history, limit.unwrap_or(RECENT_SESSIONS)))
+}
+
+// 所有实例的统计，最近玩过的在前，用于按最近使用排序
+#[tauri::command]
+pub fn list_instance_playtime() -> Vec<PlaytimeSummary> {
+    let mut summaries: Vec<PlaytimeSummary> = Instance::list()
+        .iter()
+        .map(|instance| summarize(&instance.id, &load_history(&instance.dir), 1))
+        .collect();
+    summaries.sort_by_key(|summary| Reverse(summary.last_played));
+    summaries
+}
+
+// 账号在所有实例中的游戏时长
+#[tauri::command]
+pub fn get_account_playtime(username: String) -> AccountPlaytime {
+    let records: Vec<SessionRecord> = Instance::list()
+        .iter()
+        .flat_map(|instance| load_history(&instance.dir))
+        .filter(|record| record.username == username)
+        .collect();
+    AccountPlaytime {
+        total_secs: records.iter().map(|record| record.duration_secs()).sum(),
+        session_count: records.len(),
+        last_played: records.iter().map(|record| record.ended_at).max(),
+        username,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(username: &str, started_at: u64, ended_at: u64, crashed: bool) -> SessionRecord {
+        SessionRecord {
+            session_id: format!("{}-1", started_at),
+            started_at,
+            ended_at,
+            exit_code: Some(if crashed { 1 } else { 0 }),
+            username: username.to_string(),
+            java_path: "java".to_string(),
+            crashed,
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let history = vec![
+            record("Steve", 1000, 4600, false),
+            record("Alex", 5000, 5600, true),
+            record("Steve", 8000, 9800, false),
+        ];
+        let summary = summarize("test", &history, 2);
+
+        assert_eq!(summary.total_secs, 6000);
+        assert_eq!(summary.session_count, 3);
+        assert_eq!(summary.crash_count, 1);
+        assert_eq!(summary.last_played, Some(9800));
+        assert_eq!(summary.accounts[0].username, "Steve");
+        assert_eq!(summary.accounts[0].total_secs, 5400);
+        assert_eq!(summary.accounts[1].last_played, Some(5600));
+        assert_eq!(
+            summary.recent.iter().map(|record| record.started_at).collect::<Vec<_>>(),
+            vec![8000, 5000]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+// ***
+// 从其他启动器导入游戏目录
+// ***
+
+use super::instance::{Instance, InstanceConfig};
+use crate::module::download::paths::MinecraftPaths;
+use crate::Setting::launcher_setting::{to_stored_path, LauncherSetting};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+// 导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub game_dir: String,
+    pub imported: Vec<Instance>,
+    pub skipped: Vec<String>, // 跳过的配置及原因
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn import_minecraft_dir(path: String) -> Result<ImportReport, String> {
+    let base_dir = PathBuf::from(&path);
+    if !base_dir.join("versions").is_dir() {
+        return Err(format!("{} 不是有效的游戏目录，缺少 versions 文件夹", path));
+    }
+
+    // 原地登记并切换到该目录，libraries/assets/versions 直接复用
+    let mut setting = LauncherSetting::load();
+    let name = base_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.clone());
+    setting.add_game_dir(format!("导入: {}", name), &base_dir);
+    setting.selected_game_dir = Some(to_stored_path(&base_dir));
+    setting.save().map_err(|e| format!("保存设置失败: {}", e))?;
+
+    let paths = MinecraftPaths::from_base_dir(base_dir.clone());
+    let mut report = ImportReport {
+        game_dir: path,
+        imported: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    // 已存在的实例不重复导入
+    let mut existing: HashSet<(String, String)> = Instance::list()
+        .iter()
+        .map(|instance| {
+            (
+                instance.config.launch_version().to_string(),
+                to_stored_path(&instance.game_dir()),
+            )
+        })
+        .collect();
+    let mut covered_versions = HashSet::new();
+
+    let mut configs = read_official_profiles(&paths, &mut report.skipped);
+    for config in &configs {
+        covered_versions.insert(config.launch_version().to_string());
+    }
+
+    // 官方启动器没有配置的版本，按HMCL/PCL的版本隔离设置导入
+    for version_id in installed_versions(&paths) {
+        if !covered_versions.contains(&version_id) {
+            configs.push(read_version_config(&paths, &version_id));
+        }
+    }
+
+    for config in configs {
+        let key = (
+            config.launch_version().to_string(),
+            config.game_dir.clone().unwrap_or_default(),
+        );
+        if !existing.insert(key) {
+            report.skipped.push(format!("{}: 已导入", config.name));
+            continue;
+        }
+        match Instance::create(config) {
+            Ok(instance) => report.imported.push(instance),
+            Err(e) => report.skipped.push(e),
+        }
+    }
+
+    Ok(report)
+}
+
+// 已安装的版本
+fn installed_versions(paths: &MinecraftPaths) -> Vec<String> {
+    let mut versions: Vec<String> = std::fs::read_dir(&paths.versions_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|id| paths.get_version_json_path(id).is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort();
+    versions
+}
+
+// 根据版本json补全游戏版本与加载器
+fn version_config(paths: &MinecraftPaths, version_id: &str, name: String) -> InstanceConfig {
+    let game_version = paths
+        .read_version_json(version_id)
+        .ok()
+        .and_then(|json| json["inheritsFrom"].as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| version_id.to_string());
+
+    let lower = version_id.to_lowercase();
+    let loader = ["neoforge", "forge", "fabric", "quilt"]
+        .into_iter()
+        .find(|loader| lower.contains(loader))
+        .map(|loader| loader.to_string());
+
+    InstanceConfig {
+        name,
+        version_id: (game_version != version_id).then(|| version_id.to_string()),
+        game_version,
+        loader,
+        ..Default::default()
+    }
+}
+
+// 读取官方启动器的 launcher_profiles.json
+fn read_official_profiles(paths: &MinecraftPaths, skipped: &mut Vec<String>) -> Vec<InstanceConfig> {
+    let Ok(content) = std::fs::read_to_string(paths.base_dir.join("launcher_profiles.json")) else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        skipped.push("launcher_profiles.json: 解析失败".to_string());
+        return Vec::new();
+    };
+    let Some(profiles) = json["profiles"].as_object() else {
+        return Vec::new();
+    };
+
+    let mut configs = Vec::new();
+    for profile in profiles.values() {
+        let Some(version_id) = profile["lastVersionId"].as_str() else {
+            continue;
+        };
+        let name = profile["name"]
+            .as_str()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(version_id)
+            .to_string();
+
+        // latest-release 等类型的配置没有固定版本
+        if !paths.get_version_json_path(version_id).is_file() {
+            skipped.push(format!("{}: 版本 {} 未安装", name, version_id));
+            continue;
+        }
+
+        let mut config = version_config(paths, version_id, name);
+        config.game_dir = Some(to_stored_path(
+            &profile["gameDir"]
+                .as_str()
+                .map(|dir| resolve_dir(&paths.base_dir, dir))
+                .unwrap_or_else(|| paths.base_dir.clone()),
+        ));
+        if let Some(java_args) = profile["javaArgs"].as_str() {
+            config.jvm_args = java_args.split_whitespace().map(|s| s.to_string()).collect();
+        }
+        config.java_path = profile["javaDir"].as_str().map(|s| s.to_string());
+        config.icon = profile["icon"].as_str().map(|s| s.to_string());
+        configs.push(config);
+    }
+    configs
+}
+
+// 读取HMCL与PCL的版本设置
+fn read_version_config(paths: &MinecraftPaths, version_id: &str) -> InstanceConfig {
+    let version_dir = paths.get_version_dir(version_id);
+    let mut config = version_config(paths, version_id, version_id.to_string());
+
+    // 没有任何设置时，存在独立的mods/saves目录视为开启了版本隔离
+    let mut isolated = version_dir.join("mods").is_dir() || version_dir.join("saves").is_dir();
+    let mut custom_dir = None;
+
+    if let Some(hmcl) = std::fs::read_to_string(version_dir.join("hmclversion.cfg"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+    {
+        // gameDirType: 0 根目录, 1 版本隔离, 2 自定义
+        match hmcl_game_dir_type(&hmcl["gameDirType"]) {
+            Some(0) => isolated = false,
+            Some(1) => isolated = true,
+            Some(2) => custom_dir = hmcl["gameDir"].as_str().map(|dir| resolve_dir(&paths.base_dir, dir)),
+            _ => {}
+        }
+        if let Some(java_args) = hmcl["javaArgs"].as_str() {
+            config.jvm_args = java_args.split_whitespace().map(|s| s.to_string()).collect();
+        }
+        config.min_memory = json_u32(&hmcl["minMemory"]);
+        config.max_memory = json_u32(&hmcl["maxMemory"]);
+        config.java_path = hmcl["defaultJavaPath"]
+            .as_str()
+            .filter(|java| !java.is_empty())
+            .map(|java| java.to_string());
+    }
+
+    if let Ok(content) = std::fs::read_to_string(version_dir.join("PCL").join("Setup.ini")) {
+        let setup = parse_pcl_ini(&content);
+        // VersionArgumentIndie: -1 跟随全局, 0 关闭, 1 开启
+        match setup.get("VersionArgumentIndie").map(|v| v.as_str()) {
+            Some("0") | Some("False") => isolated = false,
+            Some("1") | Some("True") => isolated = true,
+            _ => {}
+        }
+        if let Some(jvm) = setup.get("VersionAdvanceJvm").filter(|v| !v.is_empty()) {
+            config.jvm_args = jvm.split_whitespace().map(|s| s.to_string()).collect();
+        }
+    }
+
+    let game_dir = custom_dir.unwrap_or_else(|| {
+        if isolated {
+            version_dir.clone()
+        } else {
+            paths.base_dir.clone()
+        }
+    });
+    config.game_dir = Some(to_stored_path(&game_dir));
+    config
+}
+
+// HMCL不同版本分别以序号或名称保存gameDirType
+fn hmcl_game_dir_type(value: &serde_json::Value) -> Option<u64> {
+    value.as_u64().or_else(|| match value.as_str()? {
+        "ROOT_FOLDER" => Some(0),
+        "VERSION_FOLDER" => Some(1),
+        "CUSTOM" => Some(2),
+        other => other.parse().ok(),
+    })
+}
+
+fn json_u32(value: &serde_json::Value) -> Option<u32> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str()?.parse().ok())
+        .filter(|v| *v > 0)
+        .map(|v| v as u32)
+}
+
+// PCL的ini每行格式为 键:值
+fn parse_pcl_ini(content: &str) -> std::collections::HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+// 相对路径按游戏目录解析
+fn resolve_dir(base_dir: &Path, dir: &str) -> PathBuf {
+    let path = Path::new(dir);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pcl_ini() {
+        let setup = parse_pcl_ini("VersionArgumentIndie:1\r\nVersionAdvanceJvm:-XX:+UseG1GC -Dfoo=bar\r\n");
+        assert_eq!(setup.get("VersionArgumentIndie").unwrap(), "1");
+        assert_eq!(setup.get("VersionAdvanceJvm").unwrap(), "-XX:+UseG1GC -Dfoo=bar");
+    }
+
+    #[test]
+    fn test_hmcl_game_dir_type() {
+        assert_eq!(hmcl_game_dir_type(&serde_json::json!(1)), Some(1));
+        assert_eq!(hmcl_game_dir_type(&serde_json::json!("CUSTOM")), Some(2));
+        assert_eq!(hmcl_game_dir_type(&serde_json::Value::Null), None);
+    }
+}
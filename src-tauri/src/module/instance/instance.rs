@@ -0,0 +1,326 @@
+// ***
+// 实例管理
+// ***
+
+use super::history::HISTORY_FILE;
+use crate::module::download::paths::MinecraftPaths;
+use crate::module::start_game::jvm_flags::JvmPreset;
+use crate::module::start_game::process::list_running_games;
+use crate::Setting::launcher_setting::{resolve_stored_path, to_stored_path};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const INSTANCE_FILE: &str = "instance.json";
+
+// 克隆实例时不复制游戏记录与每次启动的日志，新实例的游戏时长从零开始
+const CLONE_EXCLUDED: [&str; 2] = [HISTORY_FILE, "logs"];
+
+// 每个实例独立的目录，libraries/assets/versions 仍然共享
+const INSTANCE_SUB_DIRS: [&str; 4] = ["mods", "config", "saves", "resourcepacks"];
+
+// instance.json 的内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceConfig {
+    pub name: String,
+    pub game_version: String,           // 游戏版本
+    pub version_id: Option<String>,     // 安装加载器后的版本id，为空时使用游戏版本
+    pub loader: Option<String>,         // fabric / forge / neoforge / quilt
+    pub loader_version: Option<String>,
+    pub java_path: Option<String>,      // 为空时自动查找
+    pub min_memory: Option<u32>,        // MB
+    pub max_memory: Option<u32>,        // MB
+    pub auto_memory: bool,              // 按系统内存与模组数量自动分配，开启时忽略上面两项
+    pub jvm_args: Vec<String>,
+    pub jvm_preset: JvmPreset,          // jvm参数预设，jvm_args 在其后可覆盖预设
+    pub icon: Option<String>,
+    pub group: Option<String>,
+    pub game_dir: Option<String>,       // 自定义 --gameDir，为空时使用实例目录
+    pub window_width: Option<u32>,      // 窗口大小，为空时使用游戏默认值
+    pub window_height: Option<u32>,
+    pub fullscreen: bool,
+    pub use_argfile: Option<bool>,      // 使用 @argfile 传递jvm参数，为空时按命令长度自动决定
+    pub wrapper_command: Option<String>, // 包装命令，如 gamemoderun、prime-run
+    pub env: BTreeMap<String, String>,  // 游戏与启动命令的额外环境变量
+    pub pre_launch_command: Option<String>, // 启动前执行，失败时取消启动
+    pub post_exit_command: Option<String>,  // 游戏退出后执行
+}
+
+// 实例
+#[derive(Debug, Clone, Serialize)]
+pub struct Instance {
+    pub id: String,
+    pub dir: PathBuf,
+    #[serde(flatten)]
+    pub config: InstanceConfig,
+}
+
+impl InstanceConfig {
+    // 实际启动的版本id
+    pub fn launch_version(&self) -> &str {
+        self.version_id.as_deref().unwrap_or(&self.game_version)
+    }
+
+    // 指定的Java路径，便携模式下可能为相对路径
+    pub fn java_executable(&self) -> Option<PathBuf> {
+        self.java_path
+            .as_deref()
+            .filter(|java_path| !java_path.is_empty())
+            .map(resolve_stored_path)
+    }
+
+    // 保存前把路径转换为保存形式，便携模式下启动器目录内的路径改为相对路径
+    fn store_paths(&mut self) {
+        for path in [&mut self.java_path, &mut self.game_dir].into_iter().flatten() {
+            if !path.is_empty() {
+                *path = to_stored_path(Path::new(path));
+            }
+        }
+    }
+
+    // 包装命令按空白分割
+    pub fn wrapper_args(&self) -> Vec<String> {
+        self.wrapper_command
+            .as_deref()
+            .map(|wrapper| wrapper.split_whitespace().map(|s| s.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    // 额外的环境变量
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        self.env.iter().map(|(key, value)| (key.clone(), value.clone())).collect()
+    }
+
+    // 内存参数与用户jvm参数
+    pub fn startup_parameter(&self) -> String {
+        self.startup_parameter_with_memory(self.min_memory, self.max_memory)
+    }
+
+    // 使用指定的内存代替配置中的内存
+    pub fn startup_parameter_with_memory(&self, min_memory: Option<u32>, max_memory: Option<u32>) -> String {
+        let mut args = Vec::new();
+        if let Some(min) = min_memory {
+            args.push(format!("-Xms{}m", min));
+        }
+        if let Some(max) = max_memory {
+            args.push(format!("-Xmx{}m", max));
+        }
+        args.extend(self.jvm_args.iter().cloned());
+        args.join(" ")
+    }
+}
+
+impl Instance {
+    // 游戏运行目录
+    pub fn game_dir(&self) -> PathBuf {
+        match &self.config.game_dir {
+            Some(game_dir) if !game_dir.is_empty() => resolve_stored_path(game_dir),
+            _ => self.dir.clone(),
+        }
+    }
+
+    // 读取实例
+    pub fn load(id: &str) -> Result<Self, String> {
+        let dir = instance_dir(id)?;
+        let content = std::fs::read_to_string(dir.join(INSTANCE_FILE))
+            .map_err(|e| format!("读取实例 {} 失败: {}", id, e))?;
+        let config = serde_json::from_str(&content).map_err(|e| format!("实例配置解析错误: {}", e))?;
+        Ok(Self {
+            id: id.to_string(),
+            dir,
+            config,
+        })
+    }
+
+    // 保存实例配置
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(&self.config).map_err(|e| e.to_string())?;
+        std::fs::write(self.dir.join(INSTANCE_FILE), content).map_err(|e| format!("保存实例配置失败: {}", e))
+    }
+
+    // 新建实例，id由名称生成
+    pub fn create(mut config: InstanceConfig) -> Result<Self, String> {
+        check_name(&config.name)?;
+        config.store_paths();
+        let id = unique_instance_id(&config.name)?;
+        let dir = instance_dir(&id)?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建实例目录失败: {}", e))?;
+
+        let instance = Self { id, dir, config };
+        for sub_dir in INSTANCE_SUB_DIRS {
+            std::fs::create_dir_all(instance.game_dir().join(sub_dir))
+                .map_err(|e| format!("创建实例目录失败: {}", e))?;
+        }
+        instance.save()?;
+        Ok(instance)
+    }
+
+    // 列出当前游戏目录下的所有实例
+    pub fn list() -> Vec<Self> {
+        let instances_dir = MinecraftPaths::new().instances_dir;
+        let Ok(entries) = std::fs::read_dir(&instances_dir) else {
+            return Vec::new();
+        };
+
+        let mut instances: Vec<Self> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join(INSTANCE_FILE).is_file())
+            .filter_map(|entry| Self::load(&entry.file_name().to_string_lossy()).ok())
+            .collect();
+        instances.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+        instances
+    }
+}
+
+// 实例目录，id不允许包含路径
+fn instance_dir(id: &str) -> Result<PathBuf, String> {
+    let valid = !id.is_empty()
+        && id != "."
+        && id != ".."
+        && Path::new(id).file_name().map_or(false, |name| name == id);
+    if !valid {
+        return Err(format!("无效的实例id: {}", id));
+    }
+    Ok(MinecraftPaths::new().instances_dir.join(id))
+}
+
+// 实例名称不能为空
+fn check_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("实例名称不能为空".to_string());
+    }
+    Ok(())
+}
+
+// 由名称生成不重复的实例id
+fn unique_instance_id(name: &str) -> Result<String, String> {
+    let base: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "-_. ".contains(c) { c } else { '_' })
+        .collect();
+    let base = base.trim_matches(|c| c == '.' || c == ' ').to_string();
+    let base = if base.is_empty() { "instance".to_string() } else { base };
+
+    let mut id = base.clone();
+    let mut index = 2;
+    while instance_dir(&id)?.exists() {
+        id = format!("{}-{}", base, index);
+        index += 1;
+    }
+    Ok(id)
+}
+
+// 递归复制目录，excluded 为不复制的第一层文件或目录名
+fn copy_dir(from: &Path, to: &Path, excluded: &[&str]) -> std::io::Result<()> {
+    let walker = WalkDir::new(from)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() != 1 || !excluded.iter().any(|name| entry.file_name() == *name));
+    for entry in walker.filter_map(|e| e.ok()) {
+        let relative = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn list_instances() -> Vec<Instance> {
+    Instance::list()
+}
+
+#[tauri::command]
+pub fn create_instance(config: InstanceConfig) -> Result<Instance, String> {
+    Instance::create(config)
+}
+
+#[tauri::command]
+pub fn update_instance(instance_id: String, config: InstanceConfig) -> Result<Instance, String> {
+    check_name(&config.name)?;
+    let mut instance = Instance::load(&instance_id)?;
+    instance.config = config;
+    instance.config.store_paths();
+    instance.save()?;
+    Ok(instance)
+}
+
+#[tauri::command]
+pub fn clone_instance(instance_id: String, name: String) -> Result<Instance, String> {
+    check_name(&name)?;
+    let source = Instance::load(&instance_id)?;
+    let id = unique_instance_id(&name)?;
+    let dir = instance_dir(&id)?;
+    copy_dir(&source.dir, &dir, &CLONE_EXCLUDED).map_err(|e| format!("复制实例失败: {}", e))?;
+
+    let mut instance = Instance {
+        id,
+        dir,
+        config: source.config,
+    };
+    instance.config.name = name;
+    instance.save()?;
+    Ok(instance)
+}
+
+// 只修改显示名称，实例id与目录保持不变
+#[tauri::command]
+pub fn rename_instance(instance_id: String, name: String) -> Result<Instance, String> {
+    check_name(&name)?;
+    let mut instance = Instance::load(&instance_id)?;
+    instance.config.name = name;
+    instance.save()?;
+    Ok(instance)
+}
+
+#[tauri::command]
+pub fn delete_instance(instance_id: String) -> Result<(), String> {
+    let instance = Instance::load(&instance_id)?;
+    // 游戏运行时删除会删掉正在使用的存档与安全模式链接的目标
+    if let Some(session) = list_running_games()
+        .into_iter()
+        .find(|session| session.instance_id.as_deref() == Some(instance_id.as_str()))
+    {
+        return Err(format!("实例正在运行（进程ID: {}），请先关闭游戏", session.pid));
+    }
+    std::fs::remove_dir_all(&instance.dir).map_err(|e| format!("删除实例失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::instance::history::{load_history, summarize};
+
+    #[test]
+    fn test_clone_skips_history() {
+        let source = std::env::temp_dir().join(format!("rtl-clone-source-{}", std::process::id()));
+        let target = std::env::temp_dir().join(format!("rtl-clone-target-{}", std::process::id()));
+        for dir in ["logs", "saves/world"] {
+            std::fs::create_dir_all(source.join(dir)).unwrap();
+        }
+        std::fs::write(source.join("saves/world/level.dat"), "level").unwrap();
+        std::fs::write(source.join("logs/1-1.log"), "log").unwrap();
+        std::fs::write(
+            source.join(HISTORY_FILE),
+            r#"[{"session_id":"1","started_at":0,"ended_at":3600,"exit_code":0,"username":"Steve","java_path":"","crashed":false}]"#,
+        )
+        .unwrap();
+        assert_eq!(summarize("source", &load_history(&source), 20).total_secs, 3600);
+
+        copy_dir(&source, &target, &CLONE_EXCLUDED).unwrap();
+        assert!(target.join("saves/world/level.dat").is_file());
+        assert!(!target.join("logs").exists());
+        let summary = summarize("target", &load_history(&target), 20);
+        assert_eq!(summary.total_secs, 0);
+        assert_eq!(summary.session_count, 0);
+
+        std::fs::remove_dir_all(&source).unwrap();
+        std::fs::remove_dir_all(&target).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+// ***
+// 自动分配游戏内存
+// ***
+
+use super::instance::Instance;
+use crate::utils::system_memory::{get_system_memory, SystemMemory};
+use serde::Serialize;
+
+// 最小堆内存，低于此值新版本基本无法进入世界
+const MIN_HEAP_MB: u32 = 1024;
+// G1在更大的堆上收益有限，反而加长停顿
+const MAX_HEAP_MB: u32 = 12288;
+// 每个模组预估占用
+const PER_MOD_MB: u32 = 24;
+
+// 分配结果与理由，返回给前端展示
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryPlan {
+    pub min_mb: u32,
+    pub max_mb: u32,
+    pub total_mb: u64,
+    pub available_mb: u64,
+    pub mod_count: usize,
+    pub reasons: Vec<String>,
+}
+
+// 共享方法到前端，无论是否开启自动分配都返回建议值
+#[tauri::command]
+pub fn get_memory_plan(instance_id: String) -> Result<MemoryPlan, String> {
+    let instance = Instance::load(&instance_id)?;
+    let memory = get_system_memory().ok_or("无法读取系统内存信息")?;
+    Ok(plan_memory(memory, count_mods(&instance), &instance.config.game_version))
+}
+
+// 开启自动分配的实例使用的内存，未开启或读取系统内存失败时返回None
+pub fn plan_instance_memory(instance: &Instance) -> Option<MemoryPlan> {
+    if !instance.config.auto_memory {
+        return None;
+    }
+    let memory = get_system_memory()?;
+    Some(plan_memory(memory, count_mods(instance), &instance.config.game_version))
+}
+
+// mods文件夹中启用的模组数量
+fn count_mods(instance: &Instance) -> usize {
+    std::fs::read_dir(instance.game_dir().join("mods"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name().to_string_lossy().to_lowercase().ends_with(".jar"))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+// 按游戏版本与模组数量估算需要的内存，再限制在系统内存允许的范围内
+pub fn plan_memory(memory: SystemMemory, mod_count: usize, game_version: &str) -> MemoryPlan {
+    let mut reasons = Vec::new();
+
+    // 1.13之前的版本1G足够，之后的世界生成与渲染占用更多
+    let base = match minor_version(game_version) {
+        Some(minor) if minor < 13 => 1024,
+        _ => 2048,
+    };
+    reasons.push(format!("游戏版本 {} 基础需要 {}MB", game_version, base));
+
+    let mut wanted = base;
+    if mod_count > 0 {
+        let extra = mod_count as u32 * PER_MOD_MB;
+        wanted += extra;
+        reasons.push(format!("{} 个模组额外需要约 {}MB", mod_count, extra));
+    }
+
+    // 给系统与其他程序留出内存，小内存设备留得少一些
+    let reserve = if memory.total_mb >= 8192 { 2048 } else { 1536 };
+    let mut upper = (memory.total_mb.saturating_sub(reserve) as u32).min(MAX_HEAP_MB);
+    if (memory.available_mb as u32) < upper {
+        upper = (memory.available_mb as u32).max(MIN_HEAP_MB);
+        reasons.push(format!("当前可用内存为 {}MB，按可用内存限制", memory.available_mb));
+    }
+    let upper = (upper / 256 * 256).max(MIN_HEAP_MB);
+
+    let max_mb = round_up(wanted, 256).clamp(MIN_HEAP_MB, upper);
+    if max_mb < wanted {
+        reasons.push(format!("系统内存为 {}MB，最大内存限制为 {}MB，游戏可能卡顿", memory.total_mb, max_mb));
+    }
+
+    // 初始堆取一半，减少启动时的占用
+    let min_mb = round_up(max_mb / 2, 256).max(512).min(max_mb);
+    reasons.push(format!("分配 -Xms{}m -Xmx{}m", min_mb, max_mb));
+
+    MemoryPlan {
+        min_mb,
+        max_mb,
+        total_mb: memory.total_mb,
+        available_mb: memory.available_mb,
+        mod_count,
+        reasons,
+    }
+}
+
+// 1.20.1 -> 20，快照等无法解析的版本视为新版本
+fn minor_version(game_version: &str) -> Option<u32> {
+    let mut parts = game_version.split('.');
+    if parts.next()? != "1" {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+fn round_up(value: u32, step: u32) -> u32 {
+    value.div_ceil(step) * step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(total_mb: u64, available_mb: u64) -> SystemMemory {
+        SystemMemory { total_mb, available_mb }
+    }
+
+    #[test]
+    fn test_plan_memory() {
+        // 原版老版本
+        let plan = plan_memory(memory(16384, 12000), 0, "1.8.9");
+        assert_eq!((plan.min_mb, plan.max_mb), (512, 1024));
+
+        // 大型整合包
+        let plan = plan_memory(memory(32768, 28000), 200, "1.20.1");
+        assert_eq!((plan.min_mb, plan.max_mb), (3584, 6912));
+
+        // 4G笔记本
+        let plan = plan_memory(memory(4096, 2500), 150, "1.20.1");
+        assert_eq!(plan.max_mb, 2304);
+        assert!(plan.max_mb as u64 <= plan.total_mb - 1536);
+
+        // 可用内存很少时不低于下限
+        let plan = plan_memory(memory(4096, 600), 0, "1.20.1");
+        assert_eq!(plan.max_mb, MIN_HEAP_MB);
+    }
+
+    #[test]
+    fn test_minor_version() {
+        assert_eq!(minor_version("1.12.2"), Some(12));
+        assert_eq!(minor_version("24w14a"), None);
+    }
+}
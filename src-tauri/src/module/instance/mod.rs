@@ -0,0 +1,4 @@
+pub mod instance;
+pub mod import;
+pub mod memory;
+pub mod history;
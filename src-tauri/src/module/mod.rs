@@ -1,2 +1,5 @@
 pub mod download;
-pub mod start_game;
\ No newline at end of file
+pub mod start_game;
+pub mod instance;
+pub mod crash;
+pub mod mods;
@@ -0,0 +1,323 @@
+// ***
+// 二分排查导致崩溃的模组，每次通过安全模式的覆盖目录只启用部分模组，不改动用户文件
+// ***
+
+use super::metadata::{scan_mods, ModInfo};
+use crate::api::account::Account;
+use crate::module::instance::instance::Instance;
+use crate::module::start_game::process::{is_running, kill_game};
+use crate::module::start_game::safe_mode::SafeMode;
+use crate::module::start_game::stg_main::StartGame;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// 出现时资源已开始加载，但主菜单还未显示
+const LOADED_MARKERS: [&str; 2] = ["Sound engine started", "OpenAL initialized"];
+// 出现加载标记后游戏在这段时间内没有崩溃，才认为已正常进入主菜单
+const STARTED_GRACE: Duration = Duration::from_secs(30);
+// 出现后认为游戏已崩溃，部分加载器会停在错误窗口而不退出
+const CRASHED_MARKERS: [&str; 5] = [
+    "---- Minecraft Crash Report ----",
+    "#@!@# Game crashed!",
+    "Mod loading has failed",
+    "Incompatible mods found!",
+    "Mod resolution failed",
+];
+// 单次测试的最长时间，超时视为失败
+const TRIAL_TIMEOUT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// 同一时间只进行一次排查
+static BISECT_RUNNING: AtomicBool = AtomicBool::new(false);
+static BISECT_CANCELLED: AtomicBool = AtomicBool::new(false);
+const CANCELLED: &str = "模组排查已取消";
+
+// 一次测试启动的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialOutcome {
+    Started,
+    Crashed,
+    TimedOut,
+}
+
+// 一次测试启动，同时作为 bisect-step 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct BisectStep {
+    pub step: usize,
+    pub enabled: Vec<String>, // 启用的模组文件名
+    pub outcome: TrialOutcome,
+    pub duration_secs: u64,
+}
+
+// 排查结论
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BisectResult {
+    Culprit { file_name: String },               // 单个模组导致崩溃
+    Conflict { first: String, second: String },  // 两个模组同时启用时崩溃
+    NotReproduced,                               // 启用全部模组时没有崩溃
+    NotModRelated,                               // 不加载模组时同样崩溃
+    Cancelled,                                   // 排查被取消，steps 中为已完成的测试
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BisectReport {
+    pub result: BisectResult,
+    pub mod_count: usize,
+    pub steps: Vec<BisectStep>,
+}
+
+// 共享方法到前端，每次测试后发送 bisect-step 事件
+#[tauri::command]
+pub async fn bisect_mods(app: AppHandle, instance_id: String, username: String) -> Result<BisectReport, String> {
+    if BISECT_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("已有正在进行的模组排查".to_string());
+    }
+    BISECT_CANCELLED.store(false, Ordering::SeqCst);
+    let result = tokio::task::spawn_blocking(move || {
+        let instance = Instance::load(&instance_id)?;
+        run_bisect(&instance, &username, Some(app))
+    })
+    .await
+    .map_err(|e| e.to_string());
+    BISECT_RUNNING.store(false, Ordering::SeqCst);
+    result?
+}
+
+// 停止排查，正在进行的测试会结束游戏，返回已完成的步骤
+#[tauri::command]
+pub fn cancel_bisect() {
+    BISECT_CANCELLED.store(true, Ordering::SeqCst);
+}
+
+fn run_bisect(instance: &Instance, username: &str, app: Option<AppHandle>) -> Result<BisectReport, String> {
+    let mods = scan_mods(&instance.game_dir().join("mods"));
+    if mods.is_empty() {
+        return Err("实例中没有启用的模组".to_string());
+    }
+
+    let mut steps = Vec::new();
+    let result = bisect(&mods, |enabled| {
+        if BISECT_CANCELLED.load(Ordering::SeqCst) {
+            return Err(CANCELLED.to_string());
+        }
+        let started = Instant::now();
+        let outcome = run_trial(instance, username, enabled, app.clone())?;
+        let step = BisectStep {
+            step: steps.len() + 1,
+            enabled: enabled.to_vec(),
+            outcome,
+            duration_secs: started.elapsed().as_secs(),
+        };
+        println!("模组排查第 {} 次: 启用 {} 个模组，结果 {:?}", step.step, enabled.len(), outcome);
+        if let Some(app) = &app {
+            let _ = app.emit("bisect-step", step.clone());
+        }
+        steps.push(step);
+        Ok(outcome != TrialOutcome::Started)
+    });
+    let result = match result {
+        Err(_) if BISECT_CANCELLED.load(Ordering::SeqCst) => BisectResult::Cancelled,
+        result => result?,
+    };
+
+    Ok(BisectReport {
+        result,
+        mod_count: mods.len(),
+        steps,
+    })
+}
+
+// 只启用指定模组启动一次，等待进入主菜单或崩溃，进入主菜单后结束游戏
+// 排查被取消时结束本次测试并返回错误
+fn run_trial(instance: &Instance, username: &str, enabled: &[String], app: Option<AppHandle>) -> Result<TrialOutcome, String> {
+    let safe_mode = SafeMode {
+        keep_mods: enabled.to_vec(),
+        fresh_config: false,
+    };
+    let mut start_game = StartGame::from_instance(instance, Account::offline(username.to_string()), None, Some(safe_mode))?;
+    start_game.diagnostic = true;
+    let session = start_game.start_game(app)?;
+
+    let started = Instant::now();
+    let mut loaded_at: Option<Instant> = None;
+    let mut offset = 0;
+    let outcome = loop {
+        thread::sleep(POLL_INTERVAL);
+        if BISECT_CANCELLED.load(Ordering::SeqCst) {
+            break None;
+        }
+        let log = read_new_log(&session.log_file, &mut offset);
+        if CRASHED_MARKERS.iter().any(|marker| log.contains(marker)) || !is_running(&session.session_id) {
+            break Some(TrialOutcome::Crashed);
+        }
+        if loaded_at.is_none() && LOADED_MARKERS.iter().any(|marker| log.contains(marker)) {
+            loaded_at = Some(Instant::now());
+        }
+        if matches!(loaded_at, Some(loaded_at) if loaded_at.elapsed() >= STARTED_GRACE) {
+            break Some(TrialOutcome::Started);
+        }
+        if started.elapsed() > TRIAL_TIMEOUT {
+            break Some(TrialOutcome::TimedOut);
+        }
+    };
+
+    // 结束游戏并等待退出，覆盖目录在退出后删除
+    if is_running(&session.session_id) {
+        let _ = kill_game(session.session_id.clone());
+    }
+    while is_running(&session.session_id) {
+        thread::sleep(POLL_INTERVAL);
+    }
+    outcome.ok_or_else(|| CANCELLED.to_string())
+}
+
+// 读取日志文件中上次之后新增的内容
+fn read_new_log(log_file: &Path, offset: &mut u64) -> String {
+    let mut content = Vec::new();
+    if let Ok(mut file) = File::open(log_file) {
+        if file.seek(SeekFrom::Start(*offset)).is_ok() {
+            if let Ok(read) = file.read_to_end(&mut content) {
+                *offset += read as u64;
+            }
+        }
+    }
+    String::from_utf8_lossy(&content).into_owned()
+}
+
+// 二分查找导致崩溃的模组或模组组合，trial 返回启用给定模组时是否失败
+// 每次启用的模组都会补上必需的前置
+pub fn bisect<F>(mods: &[ModInfo], mut trial: F) -> Result<BisectResult, String>
+where
+    F: FnMut(&[String]) -> Result<bool, String>,
+{
+    let all: Vec<String> = mods.iter().map(|info| info.file_name.clone()).collect();
+    let mut fails = |enabled: &[String]| trial(&with_dependencies(mods, enabled));
+
+    if !fails(&all)? {
+        return Ok(BisectResult::NotReproduced);
+    }
+    if fails(&[])? {
+        return Ok(BisectResult::NotModRelated);
+    }
+
+    let mut suspects = all;
+    while suspects.len() > 1 {
+        let (first, second) = suspects.split_at(suspects.len() / 2);
+        if fails(first)? {
+            suspects = first.to_vec();
+            continue;
+        }
+        if fails(second)? {
+            suspects = second.to_vec();
+            continue;
+        }
+
+        // 两半分别启用都正常，说明是两半之间的冲突
+        let first = narrow(first, second, &mut fails)?;
+        let second = narrow(second, std::slice::from_ref(&first), &mut fails)?;
+        return Ok(BisectResult::Conflict { first, second });
+    }
+    Ok(BisectResult::Culprit {
+        file_name: suspects.remove(0),
+    })
+}
+
+// 与 fixed 同时启用时，找出 candidates 中导致失败的一个
+fn narrow<F>(candidates: &[String], fixed: &[String], fails: &mut F) -> Result<String, String>
+where
+    F: FnMut(&[String]) -> Result<bool, String>,
+{
+    let mut candidates = candidates.to_vec();
+    while candidates.len() > 1 {
+        let (half, rest) = candidates.split_at(candidates.len() / 2);
+        let enabled: Vec<String> = fixed.iter().chain(half).cloned().collect();
+        candidates = if fails(&enabled)? { half.to_vec() } else { rest.to_vec() };
+    }
+    Ok(candidates.remove(0))
+}
+
+// 补上必需的前置，按原有顺序返回
+fn with_dependencies(mods: &[ModInfo], enabled: &[String]) -> Vec<String> {
+    let mut included: Vec<bool> = mods.iter().map(|info| enabled.contains(&info.file_name)).collect();
+    let mut pending: Vec<usize> = (0..mods.len()).filter(|index| included[*index]).collect();
+    while let Some(index) = pending.pop() {
+        for depend in &mods[index].depends {
+            let provider = mods
+                .iter()
+                .position(|info| info.provides(depend))
+                .filter(|provider| !included[*provider]);
+            if let Some(provider) = provider {
+                included[provider] = true;
+                pending.push(provider);
+            }
+        }
+    }
+    mods.iter()
+        .zip(included)
+        .filter(|(_, included)| *included)
+        .map(|(info, _)| info.file_name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mods() -> Vec<ModInfo> {
+        (0..10)
+            .map(|index| ModInfo {
+                file_name: format!("mod{}.jar", index),
+                ids: vec![format!("mod{}", index)],
+                // mod7 依赖 mod1
+                depends: if index == 7 { vec!["mod1".to_string()] } else { Vec::new() },
+                ..ModInfo::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bisect_culprit() {
+        let mods = mods();
+        let mut trials = 0;
+        let result = bisect(&mods, |enabled| {
+            trials += 1;
+            // 启用mod7时前置必须同时启用
+            assert!(!enabled.contains(&"mod7.jar".to_string()) || enabled.contains(&"mod1.jar".to_string()));
+            Ok(enabled.contains(&"mod7.jar".to_string()))
+        })
+        .unwrap();
+        assert_eq!(
+            result,
+            BisectResult::Culprit {
+                file_name: "mod7.jar".to_string()
+            }
+        );
+        assert!(trials <= 10);
+    }
+
+    #[test]
+    fn test_bisect_conflict() {
+        let mods = mods();
+        let result = bisect(&mods, |enabled| {
+            Ok(enabled.contains(&"mod2.jar".to_string()) && enabled.contains(&"mod8.jar".to_string()))
+        })
+        .unwrap();
+        assert_eq!(
+            result,
+            BisectResult::Conflict {
+                first: "mod2.jar".to_string(),
+                second: "mod8.jar".to_string()
+            }
+        );
+        assert_eq!(bisect(&mods, |_| Ok(false)).unwrap(), BisectResult::NotReproduced);
+        assert_eq!(bisect(&mods, |_| Ok(true)).unwrap(), BisectResult::NotModRelated);
+    }
+}
@@ -0,0 +1,281 @@
+// ***
+// 读取模组jar中的元数据：id、名称、版本与必需的前置
+// ***
+
+use crate::module::instance::instance::Instance;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+// 内嵌jar的最大层数
+const MAX_NESTED_DEPTH: usize = 2;
+
+// 一个模组文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModInfo {
+    pub file_name: String,
+    pub ids: Vec<String>,       // 包括 provides 与内嵌jar中的id
+    pub name: String,
+    pub version: Option<String>,
+    pub loader: Option<String>, // fabric / quilt / forge / neoforge / legacy_forge
+    pub depends: Vec<String>,   // 必需的前置id
+}
+
+impl ModInfo {
+    // 是否提供了某个id
+    pub fn provides(&self, id: &str) -> bool {
+        self.ids.iter().any(|own| own == id)
+    }
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn list_mods(instance_id: String) -> Result<Vec<ModInfo>, String> {
+    let instance = Instance::load(&instance_id)?;
+    Ok(scan_mods(&instance.game_dir().join("mods")))
+}
+
+// mods文件夹中启用的模组，无法读取元数据的jar只有文件名
+pub fn scan_mods(mods_dir: &Path) -> Vec<ModInfo> {
+    let Ok(entries) = std::fs::read_dir(mods_dir) else {
+        return Vec::new();
+    };
+    let mut mods: Vec<ModInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| entry.file_name().to_string_lossy().to_lowercase().ends_with(".jar"))
+        .map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let mut info = read_mod(&entry.path()).unwrap_or_else(|e| {
+                println!("读取模组 {} 失败: {}", file_name, e);
+                ModInfo::default()
+            });
+            if info.name.is_empty() {
+                info.name = file_name.trim_end_matches(".jar").to_string();
+            }
+            info.file_name = file_name;
+            info
+        })
+        .collect();
+    mods.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    mods
+}
+
+// 读取单个模组jar
+pub fn read_mod(path: &Path) -> Result<ModInfo, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+    Ok(read_archive(&mut archive, 0))
+}
+
+fn read_archive<R: Read + Seek>(archive: &mut ZipArchive<R>, depth: usize) -> ModInfo {
+    let mut info = ModInfo::default();
+    let mut nested = Vec::new();
+
+    if let Some(json) = read_json(archive, "fabric.mod.json") {
+        info.loader = Some("fabric".to_string());
+        info.ids.extend(json["id"].as_str().map(|id| id.to_string()));
+        info.ids.extend(string_list(&json["provides"]));
+        info.name = json["name"].as_str().unwrap_or_default().to_string();
+        info.version = json["version"].as_str().map(|version| version.to_string());
+        if let Some(depends) = json["depends"].as_object() {
+            info.depends.extend(depends.keys().cloned());
+        }
+        nested.extend(
+            json["jars"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|jar| jar["file"].as_str())
+                .map(|file| file.to_string()),
+        );
+    } else if let Some(json) = read_json(archive, "quilt.mod.json") {
+        let loader = &json["quilt_loader"];
+        info.loader = Some("quilt".to_string());
+        info.ids.extend(loader["id"].as_str().map(|id| id.to_string()));
+        info.ids.extend(loader["provides"].as_array().into_iter().flatten().filter_map(|provide| {
+            provide.as_str().or_else(|| provide["id"].as_str()).map(|id| id.to_string())
+        }));
+        info.name = loader["metadata"]["name"].as_str().unwrap_or_default().to_string();
+        info.version = loader["version"].as_str().map(|version| version.to_string());
+        for depend in loader["depends"].as_array().into_iter().flatten() {
+            match depend {
+                Value::String(id) => info.depends.push(id.clone()),
+                _ if !depend["optional"].as_bool().unwrap_or(false) => {
+                    info.depends.extend(depend["id"].as_str().map(|id| id.to_string()))
+                }
+                _ => {}
+            }
+        }
+        nested.extend(string_list(&loader["jars"]));
+    } else if let Some((loader, content)) = [("neoforge", "META-INF/neoforge.mods.toml"), ("forge", "META-INF/mods.toml")]
+        .iter()
+        .find_map(|(loader, name)| read_text(archive, name).map(|content| (loader, content)))
+    {
+        info.loader = Some(loader.to_string());
+        parse_mods_toml(&content, &mut info);
+    } else if let Some(json) = read_json(archive, "mcmod.info") {
+        // 旧版Forge，可能是数组或 {"modList": [...]}
+        info.loader = Some("legacy_forge".to_string());
+        let list = json.as_array().or_else(|| json["modList"].as_array()).cloned().unwrap_or_default();
+        for entry in &list {
+            info.ids.extend(entry["modid"].as_str().map(|id| id.to_string()));
+            info.depends.extend(string_list(&entry["requiredMods"]));
+        }
+        if let Some(first) = list.first() {
+            info.name = first["name"].as_str().unwrap_or_default().to_string();
+            info.version = first["version"].as_str().map(|version| version.to_string());
+        }
+    }
+
+    // Forge的jarjar
+    if let Some(json) = read_json(archive, "META-INF/jarjar/metadata.json") {
+        nested.extend(
+            json["jars"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|jar| jar["path"].as_str())
+                .map(|path| path.to_string()),
+        );
+    }
+
+    // 内嵌jar提供的id也算作该模组的id，前置指向内嵌库时保留外层模组即可
+    if depth < MAX_NESTED_DEPTH {
+        for path in nested {
+            let Some(bytes) = read_bytes(archive, &path) else {
+                continue;
+            };
+            if let Ok(mut inner) = ZipArchive::new(Cursor::new(bytes)) {
+                info.ids.extend(read_archive(&mut inner, depth + 1).ids);
+            }
+        }
+    }
+    let mut seen = Vec::new();
+    info.ids.retain(|id| {
+        let first = !seen.contains(id);
+        seen.push(id.clone());
+        first
+    });
+    info
+}
+
+// mods.toml 的简单解析，只读取 [[mods]] 与 [[dependencies.*]] 中需要的键
+fn parse_mods_toml(content: &str, info: &mut ModInfo) {
+    let mut section = String::new();
+    let mut dependency: Option<(String, bool)> = None;
+
+    for line in content.lines() {
+        let mut line = line.trim();
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            line = line.split('#').next().unwrap_or(line).trim();
+        }
+        if let Some(name) = line.strip_prefix("[[").and_then(|line| line.strip_suffix("]]")) {
+            finish_dependency(&mut dependency, info);
+            section = name.trim().to_string();
+            if section.starts_with("dependencies.") {
+                dependency = Some((String::new(), false));
+            }
+            continue;
+        }
+        if line.starts_with('[') {
+            finish_dependency(&mut dependency, info);
+            section = String::new();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.split('#').next().unwrap_or(value).trim().trim_matches(|c| c == '"' || c == '\'');
+
+        if section == "mods" {
+            match key {
+                "modId" => info.ids.push(value.to_string()),
+                "displayName" if info.name.is_empty() => info.name = value.to_string(),
+                "version" if info.version.is_none() && !value.starts_with("${") => info.version = Some(value.to_string()),
+                _ => {}
+            }
+        } else if let Some((id, required)) = &mut dependency {
+            match key {
+                "modId" => *id = value.to_string(),
+                "mandatory" => *required = value == "true",
+                "type" => *required = value.eq_ignore_ascii_case("required"),
+                _ => {}
+            }
+        }
+    }
+    finish_dependency(&mut dependency, info);
+}
+
+// 必需的前置在该段结束时记录
+fn finish_dependency(dependency: &mut Option<(String, bool)>, info: &mut ModInfo) {
+    if let Some((id, true)) = dependency.take() {
+        info.depends.push(id);
+    }
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.as_str())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+fn read_bytes<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<Vec<u8>> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn read_text<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<String> {
+    read_bytes(archive, name).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn read_json<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<Value> {
+    // 部分模组的json带有BOM或控制字符
+    let text = read_text(archive, name)?;
+    let text = text.trim_start_matches('\u{feff}').replace(['\n', '\r', '\t'], " ");
+    serde_json::from_str(&text).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mods_toml() {
+        let content = r#"
+modLoader="javafml"
+[[mods]] # 主模组
+modId="create"
+version="${file.jarVersion}"
+displayName="Create"
+[[dependencies.create]]
+    modId="forge"
+    mandatory=true
+[[dependencies.create]]
+    modId="flywheel"
+    type="required"
+[[dependencies.create]]
+    modId="jei"
+    mandatory=false
+"#;
+        let mut info = ModInfo::default();
+        parse_mods_toml(content, &mut info);
+        assert_eq!(info.ids, vec!["create"]);
+        assert_eq!(info.name, "Create");
+        assert_eq!(info.version, None);
+        assert_eq!(info.depends, vec!["forge", "flywheel"]);
+    }
+}
@@ -0,0 +1,73 @@
+// ***
+// Java 9+ 的 @argfile，把jvm参数、classpath与主类写入临时文件
+// ***
+
+use super::natives::session_temp_dir;
+use std::io::Write;
+use std::path::PathBuf;
+
+// 启动命令超过此长度时自动使用argfile，Windows的命令行上限为32767个字符
+pub const ARGFILE_THRESHOLD: usize = 8000;
+
+// 写入临时目录，返回文件路径，游戏退出后由进程管理删除
+// 文件已存在时失败，不与其他游戏共用argfile
+pub fn write_argfile(args: &[String]) -> std::io::Result<PathBuf> {
+    let path = session_temp_dir("rtlauncher-args").with_extension("args");
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let content: Vec<String> = args.iter().map(|arg| quote(arg)).collect();
+    let mut file = std::fs::OpenOptions::new().write(true).create_new(true).open(&path)?;
+    file.write_all(content.join("\n").as_bytes())?;
+    Ok(path)
+}
+
+// argfile中双引号内的反斜杠是转义符，统一加引号并转义
+fn quote(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("-Xmx4G"), "\"-Xmx4G\"");
+        assert_eq!(
+            quote("C:\\Program Files\\\"mc\""),
+            "\"C:\\\\Program Files\\\\\\\"mc\\\"\""
+        );
+    }
+
+    #[test]
+    fn test_argfile_with_java() {
+        // 有Java 9+时实际运行一次，确认转义能被正确解析
+        let Ok(output) = std::process::Command::new("java").arg("-version").output() else {
+            return;
+        };
+        if String::from_utf8_lossy(&output.stderr).contains("version \"1.") {
+            return;
+        }
+
+        let path = write_argfile(&["-Dtest.value=a b\\c\"d".to_string(), "-XshowSettings:properties".to_string(), "-version".to_string()]).unwrap();
+        let output = std::process::Command::new("java")
+            .arg(format!("@{}", path.display()))
+            .output()
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("test.value = a b\\c\"d"));
+    }
+}
@@ -0,0 +1,237 @@
+// ***
+// 版本json中的参数模板与规则
+// ***
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env::consts::OS;
+
+// 规则中的features，如 has_custom_resolution、is_demo_user
+pub type Features = HashMap<String, bool>;
+
+// 版本json中使用的系统名称
+pub fn rule_os_name() -> &'static str {
+    match OS {
+        "macos" => "osx",
+        other => other,
+    }
+}
+
+// 判断rules是否允许，没有rules时视为允许
+pub fn rules_allow(rules: Option<&Value>, features: &Features) -> bool {
+    let Some(rules) = rules.and_then(|rules| rules.as_array()) else {
+        return true;
+    };
+
+    let mut allowed = false;
+    for rule in rules {
+        if rule_matches(rule, features) {
+            allowed = rule["action"].as_str() == Some("allow");
+        }
+    }
+    allowed
+}
+
+fn rule_matches(rule: &Value, features: &Features) -> bool {
+    if let Some(os) = rule.get("os") {
+        if let Some(name) = os["name"].as_str() {
+            if name != rule_os_name() {
+                return false;
+            }
+        }
+        if let Some(arch) = os["arch"].as_str() {
+            let is_x86 = cfg!(target_pointer_width = "32");
+            if (arch == "x86") != is_x86 {
+                return false;
+            }
+        }
+        if let Some(version) = os["version"].as_str() {
+            let os_version = os_info::get().version().to_string();
+            if !simple_regex_match(version, &os_version) {
+                return false;
+            }
+        }
+    }
+
+    if let Some(required) = rule.get("features").and_then(|f| f.as_object()) {
+        for (name, value) in required {
+            let enabled = features.get(name).copied().unwrap_or(false);
+            if value.as_bool() != Some(enabled) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// 系统版本规则只用到 ^ $ \d \. 这几种写法
+fn simple_regex_match(pattern: &str, text: &str) -> bool {
+    let (anchored, pattern) = match pattern.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let (to_end, pattern) = match pattern.strip_suffix('$') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+
+    // 解析为逐字符的匹配项
+    enum Token {
+        Literal(char),
+        Digit,
+        Any,
+    }
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('d') => tokens.push(Token::Digit),
+                Some(escaped) => tokens.push(Token::Literal(escaped)),
+                None => tokens.push(Token::Literal('\\')),
+            },
+            '.' => tokens.push(Token::Any),
+            other => tokens.push(Token::Literal(other)),
+        }
+    }
+
+    let text: Vec<char> = text.chars().collect();
+    let matches_at = |start: usize| {
+        if start + tokens.len() > text.len() {
+            return false;
+        }
+        if to_end && start + tokens.len() != text.len() {
+            return false;
+        }
+        tokens.iter().zip(&text[start..]).all(|(token, c)| match token {
+            Token::Literal(expected) => expected == c,
+            Token::Digit => c.is_ascii_digit(),
+            Token::Any => true,
+        })
+    };
+
+    if anchored {
+        matches_at(0)
+    } else {
+        (0..=text.len()).any(matches_at)
+    }
+}
+
+// 展开 arguments.game / arguments.jvm，按规则过滤
+pub fn collect_arguments(arguments: &Value, features: &Features) -> Vec<String> {
+    let mut result = Vec::new();
+    for argument in arguments.as_array().into_iter().flatten() {
+        match argument {
+            Value::String(value) => result.push(value.clone()),
+            Value::Object(_) => {
+                if !rules_allow(argument.get("rules"), features) {
+                    continue;
+                }
+                match &argument["value"] {
+                    Value::String(value) => result.push(value.clone()),
+                    Value::Array(values) => {
+                        result.extend(values.iter().filter_map(|v| v.as_str()).map(|v| v.to_string()))
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+// 替换 ${name} 占位符，未知的占位符保持原样
+pub fn substitute(template: &str, variables: &HashMap<&str, String>) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match variables.get(name) {
+                    Some(value) => result.push_str(value),
+                    None => result.push_str(&rest[start..start + 2 + end + 1]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// 去掉值为空的游戏参数，如离线账号的 --xuid、--clientId
+pub fn drop_empty_options(arguments: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        if argument.is_empty() {
+            if result.last().map_or(false, |last| last.starts_with("--")) {
+                result.pop();
+            }
+            continue;
+        }
+        result.push(argument);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rules_allow() {
+        let features = Features::from([("has_custom_resolution".to_string(), true)]);
+        let rules = json!([{ "action": "allow", "features": { "has_custom_resolution": true } }]);
+        assert!(rules_allow(Some(&rules), &features));
+        assert!(!rules_allow(Some(&rules), &Features::new()));
+
+        let rules = json!([{ "action": "allow" }, { "action": "disallow", "os": { "name": rule_os_name() } }]);
+        assert!(!rules_allow(Some(&rules), &features));
+        assert!(rules_allow(None, &features));
+    }
+
+    #[test]
+    fn test_collect_and_substitute() {
+        let arguments = json!([
+            "--username",
+            "${auth_player_name}",
+            { "rules": [{ "action": "allow", "features": { "is_demo_user": true } }], "value": "--demo" },
+            { "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+              "value": ["--width", "${resolution_width}"] },
+            "--clientId",
+            "${clientid}"
+        ]);
+        let features = Features::from([("has_custom_resolution".to_string(), true)]);
+        let variables = HashMap::from([
+            ("auth_player_name", "Steve".to_string()),
+            ("resolution_width", "854".to_string()),
+            ("clientid", String::new()),
+        ]);
+
+        let arguments: Vec<String> = collect_arguments(&arguments, &features)
+            .iter()
+            .map(|argument| substitute(argument, &variables))
+            .collect();
+        assert_eq!(
+            drop_empty_options(arguments),
+            vec!["--username", "Steve", "--width", "854"]
+        );
+    }
+
+    #[test]
+    fn test_simple_regex_match() {
+        assert!(simple_regex_match("^10\\.", "10.0.19045"));
+        assert!(!simple_regex_match("^10\\.", "11.0"));
+        assert!(simple_regex_match("^10\\.5\\.\\d$", "10.5.8"));
+        assert!(!simple_regex_match("^10\\.5\\.\\d$", "10.5.10"));
+    }
+}
@@ -0,0 +1,154 @@
+// ***
+// 由版本json的libraries生成classpath
+// ***
+
+use super::arguments::{rules_allow, Features};
+use crate::module::download::paths::MinecraftPaths;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+// classpath中的一个库
+#[derive(Debug, Clone)]
+pub struct LibraryArtifact {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+// 按声明顺序选出当前平台需要的库，同一库只保留第一次出现的版本
+pub fn resolve_libraries(paths: &MinecraftPaths, version_json: &Value, features: &Features) -> Vec<LibraryArtifact> {
+    let mut seen = HashSet::new();
+    let mut artifacts = Vec::new();
+
+    for library in version_json["libraries"].as_array().into_iter().flatten() {
+        let Some(name) = library["name"].as_str() else {
+            continue;
+        };
+        if !rules_allow(library.get("rules"), features) {
+            continue;
+        }
+
+        // 只有natives的旧版库在启动前解压，不放入classpath
+        let artifact = &library["downloads"]["artifact"];
+        if library.get("natives").is_some() && !artifact.is_object() {
+            continue;
+        }
+
+        let Some(relative_path) = artifact["path"]
+            .as_str()
+            .map(|path| path.to_string())
+            .or_else(|| maven_path(name))
+        else {
+            continue;
+        };
+
+        if !seen.insert(library_key(name)) {
+            continue;
+        }
+
+        artifacts.push(LibraryArtifact {
+            name: name.to_string(),
+            path: paths.libraries_dir.join(relative_path),
+        });
+    }
+    artifacts
+}
+
+// 生成classpath，客户端jar放在最后；缺少文件时返回缺失列表
+pub fn build_classpath(
+    paths: &MinecraftPaths,
+    version_json: &Value,
+    features: &Features,
+    client_jar: PathBuf,
+) -> Result<Vec<String>, String> {
+    let libraries = resolve_libraries(paths, version_json, features);
+    let missing: Vec<String> = libraries
+        .iter()
+        .filter(|artifact| !artifact.path.is_file())
+        .map(|artifact| format!("{} ({})", artifact.name, artifact.path.display()))
+        .chain(
+            (!client_jar.is_file()).then(|| format!("客户端 ({})", client_jar.display())),
+        )
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("缺少以下库文件，请重新下载该版本:\n{}", missing.join("\n")));
+    }
+
+    let mut entries: Vec<PathBuf> = libraries.into_iter().map(|artifact| artifact.path).collect();
+    entries.push(client_jar);
+    Ok(entries
+        .into_iter()
+        .map(|path| paths.get_absolute_path(path))
+        .collect())
+}
+
+// group:artifact[:classifier]，不含版本号
+fn library_key(name: &str) -> String {
+    let name = name.split('@').next().unwrap_or(name);
+    let parts: Vec<&str> = name.split(':').collect();
+    match parts.as_slice() {
+        [group, artifact, _version, classifier, ..] => format!("{}:{}:{}", group, artifact, classifier),
+        [group, artifact, ..] => format!("{}:{}", group, artifact),
+        _ => name.to_string(),
+    }
+}
+
+// 由maven坐标推出相对路径，如 net.fabricmc:fabric-loader:0.15.7
+pub fn maven_path(name: &str) -> Option<String> {
+    let (coordinate, extension) = match name.split_once('@') {
+        Some((coordinate, extension)) => (coordinate, extension),
+        None => (name, "jar"),
+    };
+    let parts: Vec<&str> = coordinate.split(':').collect();
+    let (group, artifact, version, classifier) = match parts.as_slice() {
+        [group, artifact, version] => (*group, *artifact, *version, None),
+        [group, artifact, version, classifier] => (*group, *artifact, *version, Some(*classifier)),
+        _ => return None,
+    };
+
+    let file_name = match classifier {
+        Some(classifier) => format!("{}-{}-{}.{}", artifact, version, classifier, extension),
+        None => format!("{}-{}.{}", artifact, version, extension),
+    };
+    Some(format!("{}/{}/{}/{}", group.replace('.', "/"), artifact, version, file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_maven_path() {
+        assert_eq!(
+            maven_path("net.fabricmc:fabric-loader:0.15.7").unwrap(),
+            "net/fabricmc/fabric-loader/0.15.7/fabric-loader-0.15.7.jar"
+        );
+        assert_eq!(
+            maven_path("org.lwjgl:lwjgl:3.3.3:natives-linux").unwrap(),
+            "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar"
+        );
+        assert_eq!(
+            maven_path("de.oceanlabs.mcp:mcp_config:1.20.1@zip").unwrap(),
+            "de/oceanlabs/mcp/mcp_config/1.20.1/mcp_config-1.20.1.zip"
+        );
+    }
+
+    #[test]
+    fn test_resolve_libraries() {
+        let paths = MinecraftPaths::from_base_dir(PathBuf::from("mc"));
+        let version_json = json!({
+            "libraries": [
+                { "name": "org.ow2.asm:asm:9.6" },
+                { "name": "org.ow2.asm:asm:9.3", "downloads": { "artifact": { "path": "org/ow2/asm/asm/9.3/asm-9.3.jar" } } },
+                { "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4", "natives": { "linux": "natives-linux" },
+                  "downloads": { "classifiers": {} } },
+                { "name": "only:other-os:1", "rules": [{ "action": "allow", "os": { "name": "not-this-os" } }] }
+            ]
+        });
+
+        let libraries = resolve_libraries(&paths, &version_json, &Features::new());
+        assert_eq!(libraries.len(), 1);
+        assert_eq!(libraries[0].name, "org.ow2.asm:asm:9.6");
+    }
+}
@@ -0,0 +1,234 @@
+// ***
+// 游戏日志，解析log4j的XMLLayout输出并转发到前端
+// ***
+
+use crate::utils::redact::Redactor;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tauri::{AppHandle, Emitter};
+
+// 一条游戏日志，非XML的输出行只有 message
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LogRecord {
+    pub session_id: String,
+    pub stream: String,         // stdout / stderr
+    pub time: Option<u64>,      // unix时间戳，毫秒
+    pub level: Option<String>,
+    pub thread: Option<String>,
+    pub logger: Option<String>,
+    pub message: String,
+    pub throwable: Option<String>,
+}
+
+impl LogRecord {
+    // 写入日志文件的格式，与原版控制台输出接近
+    pub fn to_line(&self) -> String {
+        let mut line = String::new();
+        if let Some(time) = self.time {
+            line.push_str(&format!("[{}] ", format_time(time)));
+        }
+        if let (Some(thread), Some(level)) = (&self.thread, &self.level) {
+            line.push_str(&format!("[{}/{}] ", thread, level));
+        }
+        if let Some(logger) = &self.logger {
+            line.push_str(&format!("[{}]: ", logger));
+        }
+        line.push_str(&self.message);
+        if let Some(throwable) = &self.throwable {
+            line.push('\n');
+            line.push_str(throwable.trim_end());
+        }
+        line
+    }
+}
+
+// 按行输入，拼接跨行的 <log4j:Event>
+#[derive(Default)]
+pub struct Log4jParser {
+    buffer: Option<String>,
+}
+
+impl Log4jParser {
+    // 返回解析完成的记录，事件未结束时返回None
+    pub fn feed(&mut self, line: &str) -> Option<LogRecord> {
+        if self.buffer.is_none() {
+            if !line.trim_start().starts_with("<log4j:Event") {
+                if line.trim().is_empty() {
+                    return None;
+                }
+                return Some(LogRecord {
+                    message: line.to_string(),
+                    ..Default::default()
+                });
+            }
+            self.buffer = Some(String::new());
+        }
+
+        let buffer = self.buffer.as_mut().unwrap();
+        buffer.push_str(line);
+        buffer.push('\n');
+        if !line.contains("</log4j:Event>") {
+            return None;
+        }
+        let event = self.buffer.take().unwrap();
+        Some(parse_event(&event))
+    }
+
+    // 输出结束时未闭合的事件按原文返回
+    pub fn finish(&mut self) -> Option<LogRecord> {
+        self.buffer.take().map(|rest| LogRecord {
+            message: rest.trim_end().to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_event(event: &str) -> LogRecord {
+    let start_tag = event.split('>').next().unwrap_or_default();
+    LogRecord {
+        time: attribute(start_tag, "timestamp").and_then(|time| time.parse().ok()),
+        level: attribute(start_tag, "level"),
+        thread: attribute(start_tag, "thread"),
+        logger: attribute(start_tag, "logger"),
+        message: element(event, "log4j:Message").unwrap_or_default(),
+        throwable: element(event, "log4j:Throwable"),
+        ..Default::default()
+    }
+}
+
+// 读取 name="value" 形式的属性
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')? + start;
+    Some(unescape(&tag[start..end]))
+}
+
+// 读取元素内容，CDATA原样保留，其余部分反转义
+fn element(event: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = event.find(&open)? + open.len();
+    let end = event[start..].find(&close)? + start;
+
+    let mut content = &event[start..end];
+    let mut text = String::new();
+    while let Some(cdata_start) = content.find("<![CDATA[") {
+        text.push_str(&unescape(&content[..cdata_start]));
+        let rest = &content[cdata_start + 9..];
+        let cdata_end = rest.find("]]>").unwrap_or(rest.len());
+        text.push_str(&rest[..cdata_end]);
+        content = rest.get(cdata_end + 3..).unwrap_or_default();
+    }
+    text.push_str(&unescape(content));
+    Some(text)
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// UTC时间，时:分:秒
+fn format_time(millis: u64) -> String {
+    let seconds = millis / 1000 % 86400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+// 在后台线程逐行读取游戏输出，写入日志文件并发送 game-log 事件
+pub fn capture<R: Read + Send + 'static>(
+    session_id: String,
+    stream: &'static str,
+    reader: R,
+    log_file: Option<Arc<Mutex<File>>>,
+    redactor: Redactor,
+    app: Option<AppHandle>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut parser = Log4jParser::default();
+        let mut bytes = Vec::new();
+
+        let emit = |mut record: LogRecord| {
+            record.session_id = session_id.clone();
+            record.stream = stream.to_string();
+            // 写入文件和发送到前端前去掉令牌等敏感信息
+            record.message = redactor.redact(&record.message);
+            record.throwable = record.throwable.map(|throwable| redactor.redact(&throwable));
+            if let Some(log_file) = &log_file {
+                let _ = writeln!(log_file.lock().unwrap(), "{}", record.to_line());
+            }
+            if let Some(app) = &app {
+                let _ = app.emit("game-log", record);
+            }
+        };
+
+        loop {
+            bytes.clear();
+            match reader.read_until(b'\n', &mut bytes) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            // 部分系统下输出不是UTF-8，无法解码的字符直接替换
+            let line = String::from_utf8_lossy(&bytes);
+            if let Some(record) = parser.feed(line.trim_end_matches(['\r', '\n'])) {
+                emit(record);
+            }
+        }
+        if let Some(record) = parser.finish() {
+            emit(record);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log4j_event() {
+        let lines = [
+            r#"<log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1700000000000" level="ERROR" thread="Render thread">"#,
+            r#"  <log4j:Message><![CDATA[Setting user: <Steve>]]></log4j:Message>"#,
+            r#"  <log4j:Throwable><![CDATA[java.lang.IllegalStateException: boom"#,
+            r#"	at a.b.C.d(C.java:1)]]></log4j:Throwable>"#,
+            r#"</log4j:Event>"#,
+            "",
+            "plain output",
+        ];
+
+        let mut parser = Log4jParser::default();
+        let records: Vec<LogRecord> = lines.iter().filter_map(|line| parser.feed(line)).collect();
+        assert_eq!(records.len(), 2);
+
+        let event = &records[0];
+        assert_eq!(event.time, Some(1700000000000));
+        assert_eq!(event.level.as_deref(), Some("ERROR"));
+        assert_eq!(event.thread.as_deref(), Some("Render thread"));
+        assert_eq!(event.logger.as_deref(), Some("net.minecraft.client.Minecraft"));
+        assert_eq!(event.message, "Setting user: <Steve>");
+        assert!(event.throwable.as_deref().unwrap().contains("IllegalStateException"));
+        assert_eq!(
+            event.to_line().lines().next().unwrap(),
+            "[22:13:20] [Render thread/ERROR] [net.minecraft.client.Minecraft]: Setting user: <Steve>"
+        );
+
+        assert_eq!(records[1].message, "plain output");
+        assert!(records[1].level.is_none());
+    }
+
+    #[test]
+    fn test_escaped_message() {
+        let mut parser = Log4jParser::default();
+        let record = parser
+            .feed(r#"<log4j:Event logger="a" timestamp="0" level="INFO" thread="main"><log4j:Message>a &amp;&lt;b&gt;</log4j:Message></log4j:Event>"#)
+            .unwrap();
+        assert_eq!(record.message, "a &<b>");
+    }
+}
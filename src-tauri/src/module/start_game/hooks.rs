@@ -0,0 +1,61 @@
+// ***
+// 实例的启动前与退出后命令
+// ***
+
+use std::env::consts::OS;
+use std::path::Path;
+use std::process::Command;
+
+// 通过系统shell执行，env中包含 INST_* 变量
+pub fn run_hook(name: &str, command_line: &str, dir: &Path, env: &[(String, String)]) -> Result<(), String> {
+    let mut command = if OS == "windows" {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    };
+    command.current_dir(dir).envs(env.iter().map(|(key, value)| (key, value)));
+
+    println!("执行{}: {}", name, command_line);
+    let output = command
+        .output()
+        .map_err(|e| format!("{}执行失败: {}", name, e))?;
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "{}执行失败，退出码: {}\n{}",
+            name,
+            output
+                .status
+                .code()
+                .map_or("无".to_string(), |code| code.to_string()),
+            stderr.trim_end()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_hook() {
+        let dir = std::env::temp_dir();
+        let env = vec![("INST_NAME".to_string(), "test".to_string())];
+        let (ok, fail) = if OS == "windows" {
+            ("if \"%INST_NAME%\"==\"test\" (exit 0) else (exit 1)", "exit 3")
+        } else {
+            ("test \"$INST_NAME\" = test", "exit 3")
+        };
+        assert!(run_hook("启动前命令", ok, &dir, &env).is_ok());
+        assert!(run_hook("启动前命令", fail, &dir, &env)
+            .unwrap_err()
+            .contains("退出码: 3"));
+    }
+}
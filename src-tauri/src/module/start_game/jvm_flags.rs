@@ -0,0 +1,316 @@
+// ***
+// jvm参数预设，以及重复、冲突参数的检查
+// ***
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// 选择垃圾回收器的参数，同时开启多个时JVM无法启动
+const GC_SELECTORS: [&str; 7] = [
+    "UseSerialGC",
+    "UseParallelGC",
+    "UseParallelOldGC",
+    "UseConcMarkSweepGC",
+    "UseG1GC",
+    "UseZGC",
+    "UseShenandoahGC",
+];
+
+// 各预设通用的参数，保留完整堆栈便于崩溃分析
+const COMMON_FLAGS: [&str; 1] = ["-XX:-OmitStackTraceInFastThrow"];
+
+// 只在部分Java版本可用的参数：(名称前缀, 最低版本, 无法启动的版本)
+const VERSIONED_FLAGS: [(&str, Option<u64>, Option<u64>); 9] = [
+    ("UseConcMarkSweepGC", None, Some(17)),
+    ("CMS", None, Some(17)),
+    ("UseParNewGC", None, Some(11)),
+    ("AggressiveOpts", None, Some(13)),
+    ("PermSize", None, Some(17)),
+    ("MaxPermSize", None, Some(17)),
+    ("UseZGC", Some(15), None),
+    ("ZGenerational", Some(21), None),
+    ("UseShenandoahGC", Some(12), None),
+];
+
+// jvm参数预设
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JvmPreset {
+    #[default]
+    Default,         // G1
+    Aikar,           // Aikar 为服务端调优的G1参数，对大型整合包同样有效
+    ZgcGenerational, // 分代ZGC，需要Java 21+
+    LowMemory,       // 串行回收，适合小内存设备
+}
+
+// 预设说明，返回给前端展示
+#[derive(Debug, Clone, Serialize)]
+pub struct JvmPresetInfo {
+    pub preset: JvmPreset,
+    pub name: String,
+    pub description: String,
+    pub min_java: Option<u64>,
+}
+
+// 参数检查发现的问题，blocking为true时JVM无法启动
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlagProblem {
+    pub message: String,
+    pub flags: Vec<String>,
+    pub blocking: bool,
+}
+
+impl JvmPreset {
+    pub const ALL: [JvmPreset; 4] = [
+        JvmPreset::Default,
+        JvmPreset::Aikar,
+        JvmPreset::ZgcGenerational,
+        JvmPreset::LowMemory,
+    ];
+
+    pub fn info(self) -> JvmPresetInfo {
+        let (name, description) = match self {
+            JvmPreset::Default => ("默认", "G1回收器，适合大多数情况"),
+            JvmPreset::Aikar => ("Aikar", "调优过的G1参数，减少大型整合包的卡顿"),
+            JvmPreset::ZgcGenerational => ("分代ZGC", "停顿极短，需要Java 21及以上与较多内存"),
+            JvmPreset::LowMemory => ("低内存", "串行回收并及时归还内存，适合4G以下的设备"),
+        };
+        JvmPresetInfo {
+            preset: self,
+            name: name.to_string(),
+            description: description.to_string(),
+            min_java: (self == JvmPreset::ZgcGenerational).then_some(21),
+        }
+    }
+
+    // 预设的参数，java_major 未知时按预设要求的版本处理
+    pub fn flags(self, java_major: Option<u64>) -> Vec<String> {
+        let flags: Vec<&str> = match self {
+            JvmPreset::Default => vec!["-XX:+UseG1GC"],
+            JvmPreset::Aikar => vec![
+                "-XX:+UseG1GC",
+                "-XX:+ParallelRefProcEnabled",
+                "-XX:MaxGCPauseMillis=200",
+                "-XX:+UnlockExperimentalVMOptions",
+                "-XX:+DisableExplicitGC",
+                "-XX:+AlwaysPreTouch",
+                "-XX:G1NewSizePercent=30",
+                "-XX:G1MaxNewSizePercent=40",
+                "-XX:G1HeapRegionSize=8M",
+                "-XX:G1ReservePercent=20",
+                "-XX:G1HeapWastePercent=5",
+                "-XX:G1MixedGCCountTarget=4",
+                "-XX:InitiatingHeapOccupancyPercent=15",
+                "-XX:G1MixedGCLiveThresholdPercent=90",
+                "-XX:SurvivorRatio=32",
+                "-XX:+PerfDisableSharedMem",
+                "-XX:MaxTenuringThreshold=1",
+            ],
+            JvmPreset::ZgcGenerational => {
+                // Java 23起分代是默认行为，24移除了该参数
+                if matches!(java_major, Some(major) if major >= 23) {
+                    vec!["-XX:+UseZGC"]
+                } else {
+                    vec!["-XX:+UseZGC", "-XX:+ZGenerational"]
+                }
+            }
+            JvmPreset::LowMemory => vec![
+                "-XX:+UseSerialGC",
+                "-XX:MinHeapFreeRatio=10",
+                "-XX:MaxHeapFreeRatio=30",
+            ],
+        };
+        flags.iter().chain(COMMON_FLAGS.iter()).map(|flag| flag.to_string()).collect()
+    }
+}
+
+// 预设参数在前，用户参数在后；用户已设置的参数与回收器不再使用预设的值
+pub fn merge_flags(preset_flags: Vec<String>, user_flags: &[String]) -> Vec<String> {
+    let user_keys: Vec<String> = user_flags.iter().map(|flag| flag_key(flag)).collect();
+    let user_selects_gc = user_flags.iter().any(|flag| selected_gc(flag).is_some());
+
+    let mut flags: Vec<String> = preset_flags
+        .into_iter()
+        .filter(|flag| !user_keys.contains(&flag_key(flag)))
+        .filter(|flag| !(user_selects_gc && selected_gc(flag).is_some()))
+        .collect();
+    flags.extend(user_flags.iter().cloned());
+    flags
+}
+
+// 检查最终的jvm参数，java_major 未知时跳过版本相关的检查
+pub fn check_flags(flags: &[String], java_major: Option<u64>) -> Vec<FlagProblem> {
+    let mut problems = Vec::new();
+
+    // 同时选择了多个垃圾回收器
+    let mut collectors: Vec<String> = Vec::new();
+    for flag in flags {
+        if selected_gc(flag).is_some() && !collectors.contains(flag) {
+            collectors.push(flag.clone());
+        }
+    }
+    if collectors.len() > 1 {
+        problems.push(FlagProblem {
+            message: format!("同时选择了多个垃圾回收器: {}", collectors.join(" ")),
+            flags: collectors,
+            blocking: true,
+        });
+    }
+
+    // 同一参数出现多次，后面的值生效
+    let mut seen: HashMap<String, &String> = HashMap::new();
+    for flag in flags {
+        if let Some(previous) = seen.insert(flag_key(flag), flag) {
+            let message = if previous == flag {
+                format!("参数 {} 重复", flag)
+            } else {
+                format!("参数 {} 与 {} 冲突，将使用后者", previous, flag)
+            };
+            problems.push(FlagProblem {
+                message,
+                flags: vec![previous.clone(), flag.clone()],
+                blocking: false,
+            });
+        }
+    }
+
+    // 最小内存大于最大内存
+    let last_size = |prefix: &str| {
+        flags
+            .iter()
+            .rev()
+            .find_map(|flag| Some((flag, parse_size(flag.strip_prefix(prefix)?)?)))
+    };
+    if let (Some((xms, min)), Some((xmx, max))) = (last_size("-Xms"), last_size("-Xmx")) {
+        if min > max {
+            problems.push(FlagProblem {
+                message: format!("最小内存 {} 大于最大内存 {}", xms, xmx),
+                flags: vec![xms.clone(), xmx.clone()],
+                blocking: true,
+            });
+        }
+    }
+
+    // 当前Java不支持的参数
+    if let Some(major) = java_major {
+        for flag in flags {
+            let Some(name) = xx_name(flag) else {
+                continue;
+            };
+            let Some((_, since, removed)) = VERSIONED_FLAGS.iter().find(|(prefix, ..)| name.starts_with(prefix)) else {
+                continue;
+            };
+            let message = match (since, removed) {
+                (Some(since), _) if major < *since => format!("参数 {} 需要Java {}及以上，当前为Java {}", flag, since, major),
+                (_, Some(removed)) if major >= *removed => format!("参数 {} 在Java {}中已被移除", flag, major),
+                _ => continue,
+            };
+            problems.push(FlagProblem {
+                message,
+                flags: vec![flag.clone()],
+                blocking: true,
+            });
+        }
+    }
+    problems
+}
+
+// -XX:+Name、-XX:-Name、-XX:Name=value 中的 Name
+fn xx_name(flag: &str) -> Option<&str> {
+    let option = flag.strip_prefix("-XX:")?;
+    let option = option.strip_prefix(['+', '-']).unwrap_or(option);
+    Some(option.split('=').next().unwrap_or(option))
+}
+
+// 开启的垃圾回收器
+fn selected_gc(flag: &str) -> Option<&str> {
+    let name = flag.strip_prefix("-XX:+")?;
+    GC_SELECTORS.contains(&name).then_some(name)
+}
+
+// 判断重复时使用的键，值不同的同一参数键相同
+fn flag_key(flag: &str) -> String {
+    if let Some(name) = xx_name(flag) {
+        return format!("-XX:{}", name);
+    }
+    for prefix in ["-Xmx", "-Xms", "-Xss", "-Xmn"] {
+        if flag.starts_with(prefix) {
+            return prefix.to_string();
+        }
+    }
+    if flag.starts_with("-D") {
+        return flag.split('=').next().unwrap_or(flag).to_string();
+    }
+    flag.to_string()
+}
+
+// 512m、4G、1048576k、不带单位时为字节，结果单位为字节，溢出时为None
+pub fn parse_size(size: &str) -> Option<u64> {
+    let (number, multiplier) = match size.char_indices().last()? {
+        (index, c) if c.is_ascii_alphabetic() => {
+            let multiplier = match c.to_ascii_lowercase() {
+                'k' => 1 << 10,
+                'm' => 1 << 20,
+                'g' => 1 << 30,
+                't' => 1 << 40,
+                _ => return None,
+            };
+            (&size[..index], multiplier)
+        }
+        _ => (size, 1),
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn list_jvm_presets() -> Vec<JvmPresetInfo> {
+    JvmPreset::ALL.iter().map(|preset| preset.info()).collect()
+}
+
+// 编辑jvm参数时检查，java_major 为空时跳过版本相关的检查
+#[tauri::command]
+pub fn check_jvm_flags(preset: Option<JvmPreset>, startup_parameter: String, java_major: Option<u64>) -> Vec<FlagProblem> {
+    let user_flags: Vec<String> = startup_parameter.split_whitespace().map(|s| s.to_string()).collect();
+    let flags = merge_flags(preset.unwrap_or_default().flags(java_major), &user_flags);
+    check_flags(&flags, java_major)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(flags: &str) -> Vec<String> {
+        flags.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_merge_flags() {
+        let flags = merge_flags(JvmPreset::Aikar.flags(Some(17)), &args("-XX:+UseZGC -XX:MaxGCPauseMillis=50"));
+        assert!(!flags.contains(&"-XX:+UseG1GC".to_string()));
+        assert!(!flags.contains(&"-XX:MaxGCPauseMillis=200".to_string()));
+        assert_eq!(&flags[flags.len() - 2..], &args("-XX:+UseZGC -XX:MaxGCPauseMillis=50")[..]);
+        assert!(check_flags(&flags, Some(17)).is_empty());
+    }
+
+    #[test]
+    fn test_check_flags() {
+        let problems = check_flags(&args("-XX:+UseG1GC -Xms4G -Xmx2048m -XX:+UseZGC -Xmx2G -XX:+UseConcMarkSweepGC"), Some(17));
+        let blocking: Vec<&str> = problems
+            .iter()
+            .filter(|problem| problem.blocking)
+            .map(|problem| problem.flags[0].as_str())
+            .collect();
+        assert_eq!(blocking, vec!["-XX:+UseG1GC", "-Xms4G", "-XX:+UseConcMarkSweepGC"]);
+        assert!(problems.iter().any(|problem| !problem.blocking && problem.flags == args("-Xmx2048m -Xmx2G")));
+
+        // 版本未知时不检查版本
+        assert!(check_flags(&args("-XX:+UseZGC -XX:+ZGenerational"), None).is_empty());
+        assert_eq!(check_flags(&args("-XX:+UseZGC -XX:+ZGenerational"), Some(17)).len(), 1);
+        assert!(JvmPreset::ZgcGenerational.flags(Some(24)).iter().all(|flag| flag != "-XX:+ZGenerational"));
+
+        // 溢出的大小无法比较，不报错
+        assert_eq!(parse_size("99999999999G"), None);
+        assert!(check_flags(&args("-Xms1G -Xmx99999999999G"), Some(17)).is_empty());
+    }
+}
@@ -1 +1,13 @@
 pub mod stg_main;
+pub mod arguments;
+pub mod version;
+pub mod classpath;
+pub mod process;
+pub mod game_log;
+pub mod preflight;
+pub mod hooks;
+pub mod quick_play;
+pub mod argfile;
+pub mod jvm_flags;
+pub mod safe_mode;
+pub mod natives;
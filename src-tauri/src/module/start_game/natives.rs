@@ -0,0 +1,77 @@
+// ***
+// 每次启动单独的natives目录，同一版本同时运行多个游戏或重新安装时不会争用被占用的文件
+// ***
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+// 同一毫秒内多次启动时区分目录
+static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+// 把版本的natives目录复制到临时目录，版本没有natives时返回None
+// 返回的目录在游戏退出后由进程管理删除
+pub fn create_session_natives(natives_dir: &Path) -> std::io::Result<Option<PathBuf>> {
+    if !natives_dir.is_dir() {
+        return Ok(None);
+    }
+    let session_dir = session_temp_dir("rtlauncher-natives");
+
+    let result = copy_natives(natives_dir, &session_dir);
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&session_dir);
+        return Err(e);
+    }
+    Ok(Some(session_dir))
+}
+
+// 临时目录中本次启动独占的路径，以进程id、毫秒时间与序号区分
+pub fn session_temp_dir(name: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    std::env::temp_dir()
+        .join(name)
+        .join(format!("{}-{}-{}", std::process::id(), millis, SEQUENCE.fetch_add(1, Ordering::Relaxed)))
+}
+
+fn copy_natives(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in WalkDir::new(from).min_depth(1).into_iter().filter_map(|e| e.ok()) {
+        let relative = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_natives() {
+        let natives_dir = std::env::temp_dir().join(format!("rtl-natives-test-{}", std::process::id()));
+        std::fs::create_dir_all(natives_dir.join("sub")).unwrap();
+        std::fs::write(natives_dir.join("liblwjgl.so"), "lwjgl").unwrap();
+        std::fs::write(natives_dir.join("sub").join("libopenal.so"), "openal").unwrap();
+
+        // 两次启动得到不同的目录
+        let first = create_session_natives(&natives_dir).unwrap().unwrap();
+        let second = create_session_natives(&natives_dir).unwrap().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(std::fs::read_to_string(first.join("sub").join("libopenal.so")).unwrap(), "openal");
+        assert!(second.join("liblwjgl.so").is_file());
+
+        for dir in [&first, &second, &natives_dir] {
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+        assert!(create_session_natives(&natives_dir).unwrap().is_none());
+    }
+}
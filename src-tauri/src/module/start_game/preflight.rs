@@ -0,0 +1,489 @@
+// ***
+// 启动前检查，在启动Java之前找出缺失或损坏的文件与错误的设置
+// ***
+
+use super::arguments::{rules_allow, Features};
+use super::classpath::resolve_libraries;
+use super::jvm_flags::{check_flags, parse_size};
+use super::stg_main::{LaunchOptions, StartGame};
+use super::version::{natives_dir, resolve_version};
+use crate::api::account::Account;
+use crate::module::download::paths::MinecraftPaths;
+use crate::module::instance::instance::Instance;
+use crate::utils::system_memory::get_system_memory;
+use crate::utils::test_java::run_probe;
+use crate::Setting::launcher_setting::get_launcher_dir;
+use serde::{Deserialize, Serialize};
+use sha1::Digest;
+use std::collections::HashMap;
+use std::env::consts::ARCH;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const HASH_CACHE_FILE: &str = "hash_cache.json";
+
+// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    InvalidGameDir,
+    VersionMissing,
+    JavaMissing,
+    JavaUnusable,        // 自检失败，如内存参数无效
+    JavaVersionMismatch,
+    JavaArchMismatch,
+    ClientJarMissing,
+    LibraryMissing,
+    FileCorrupted,       // sha1与版本json不符
+    NativesMissing,
+    AssetIndexMissing,
+    InsufficientMemory,
+    JvmFlagConflict,     // jvm参数重复或冲突，无法启动的冲突为blocking
+}
+
+// 一个问题，blocking为true时不允许启动
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightProblem {
+    pub kind: ProblemKind,
+    pub message: String,
+    pub path: Option<PathBuf>,
+    pub auto_fixable: bool, // 重新下载或解压即可修复
+    pub blocking: bool,
+}
+
+impl PreflightProblem {
+    fn new(kind: ProblemKind, message: String) -> Self {
+        let auto_fixable = matches!(
+            kind,
+            ProblemKind::VersionMissing
+                | ProblemKind::ClientJarMissing
+                | ProblemKind::LibraryMissing
+                | ProblemKind::FileCorrupted
+                | ProblemKind::NativesMissing
+                | ProblemKind::AssetIndexMissing
+        );
+        let blocking = !matches!(kind, ProblemKind::JavaArchMismatch | ProblemKind::InsufficientMemory);
+        Self {
+            kind,
+            message,
+            path: None,
+            auto_fixable,
+            blocking,
+        }
+    }
+
+    fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub async fn preflight_instance(instance_id: String) -> Result<Vec<PreflightProblem>, String> {
+    let instance = Instance::load(&instance_id)?;
+    tokio::task::spawn_blocking(move || {
+        let (java_path, options) = StartGame::instance_options(&instance, Account::default())?;
+        Ok(run_preflight(&java_path, &options))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 依次检查游戏目录、版本、Java、文件与内存
+pub fn run_preflight(java_path: &str, options: &LaunchOptions) -> Vec<PreflightProblem> {
+    let mut problems = Vec::new();
+    check_game_dir(&options.game_dir, &mut problems);
+
+    let paths = MinecraftPaths::new();
+    let version_json = match resolve_version(&paths, &options.version_id) {
+        Ok(version_json) => version_json,
+        Err(e) => {
+            problems.push(PreflightProblem::new(ProblemKind::VersionMissing, e));
+            return problems;
+        }
+    };
+
+    let required_java = version_json["javaVersion"]["majorVersion"].as_u64().unwrap_or(8);
+    let jvm_flags = StartGame::jvm_flags(options.jvm_preset, options.java_major, &options.startup_parameter);
+    check_jvm_flags(&jvm_flags, options.java_major, &mut problems);
+    check_java(java_path, &jvm_flags, required_java, &mut problems);
+
+    let mut cache = HashCache::load();
+    check_files(&paths, &version_json, options, &mut cache, &mut problems);
+    cache.save();
+
+    check_memory(&options.startup_parameter, &mut problems);
+    problems
+}
+
+fn check_game_dir(game_dir: &Path, problems: &mut Vec<PreflightProblem>) {
+    if game_dir.exists() && !game_dir.is_dir() {
+        problems.push(
+            PreflightProblem::new(ProblemKind::InvalidGameDir, format!("游戏目录 {} 不是文件夹", game_dir.display()))
+                .with_path(game_dir),
+        );
+        return;
+    }
+
+    // 目录不存在时创建，并确认可以写入
+    let probe = game_dir.join(".rtl-write-test");
+    let writable = std::fs::create_dir_all(game_dir).and_then(|_| std::fs::write(&probe, b"")).is_ok();
+    let _ = std::fs::remove_file(&probe);
+    if !writable {
+        problems.push(
+            PreflightProblem::new(ProblemKind::InvalidGameDir, format!("游戏目录 {} 无法写入", game_dir.display()))
+                .with_path(game_dir),
+        );
+    }
+}
+
+fn check_jvm_flags(jvm_flags: &[String], java_major: Option<u64>, problems: &mut Vec<PreflightProblem>) {
+    for problem in check_flags(jvm_flags, java_major) {
+        problems.push(PreflightProblem {
+            blocking: problem.blocking,
+            ..PreflightProblem::new(ProblemKind::JvmFlagConflict, problem.message)
+        });
+    }
+}
+
+fn check_java(java_path: &str, jvm_flags: &[String], required: u64, problems: &mut Vec<PreflightProblem>) {
+    if java_path.is_empty() || !Path::new(java_path).is_file() {
+        problems.push(PreflightProblem::new(
+            ProblemKind::JavaMissing,
+            format!("找不到Java，该版本需要Java {}", required),
+        ));
+        return;
+    }
+
+    let result = match run_probe(java_path, jvm_flags, &[], &[]) {
+        Ok(result) => result,
+        Err(e) => {
+            problems.push(PreflightProblem::new(ProblemKind::JavaUnusable, e).with_path(Path::new(java_path)));
+            return;
+        }
+    };
+    if !result.success {
+        problems.push(
+            PreflightProblem::new(ProblemKind::JavaUnusable, result.message).with_path(Path::new(java_path)),
+        );
+        return;
+    }
+
+    if let Some(major) = result.java_version.as_deref().and_then(java_major_version) {
+        if major < required {
+            problems.push(
+                PreflightProblem::new(
+                    ProblemKind::JavaVersionMismatch,
+                    format!("该版本需要Java {}，当前为Java {}", required, major),
+                )
+                .with_path(Path::new(java_path)),
+            );
+        }
+    }
+
+    if let Some(arch) = result.os_arch.as_deref() {
+        if normalize_arch(arch) != normalize_arch(ARCH) {
+            problems.push(
+                PreflightProblem::new(
+                    ProblemKind::JavaArchMismatch,
+                    format!("Java的架构为 {}，与系统的 {} 不一致，可能影响性能或可用内存", arch, ARCH),
+                )
+                .with_path(Path::new(java_path)),
+            );
+        }
+    }
+}
+
+// 1.8.0_392 -> 8，17.0.2 -> 17
+pub fn java_major_version(version: &str) -> Option<u64> {
+    let mut parts = version.split(|c: char| c == '.' || c == '_' || c == '-' || c == '+');
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "amd64" | "x86_64" => "x86_64",
+        "aarch64" | "arm64" => "aarch64",
+        "x86" | "i386" | "i686" => "x86",
+        other => other,
+    }
+}
+
+fn check_files(
+    paths: &MinecraftPaths,
+    version_json: &serde_json::Value,
+    options: &LaunchOptions,
+    cache: &mut HashCache,
+    problems: &mut Vec<PreflightProblem>,
+) {
+    let features = Features::new();
+
+    // 客户端jar
+    let jar_id = version_json["jar"].as_str().unwrap_or(&options.version_id);
+    let client_jar = paths.get_version_dir(jar_id).join(format!("{}.jar", jar_id));
+    if !client_jar.is_file() {
+        problems.push(
+            PreflightProblem::new(ProblemKind::ClientJarMissing, format!("缺少客户端 {}.jar", jar_id))
+                .with_path(&client_jar),
+        );
+    } else {
+        check_sha1(&client_jar, version_json["downloads"]["client"]["sha1"].as_str(), cache, problems);
+    }
+
+    // classpath中的库
+    let mut sha1_by_name: HashMap<&str, &str> = HashMap::new();
+    for library in version_json["libraries"].as_array().into_iter().flatten() {
+        if let (Some(name), Some(sha1)) = (
+            library["name"].as_str(),
+            library["downloads"]["artifact"]["sha1"].as_str(),
+        ) {
+            sha1_by_name.entry(name).or_insert(sha1);
+        }
+    }
+    for artifact in resolve_libraries(paths, version_json, &features) {
+        if !artifact.path.is_file() {
+            problems.push(
+                PreflightProblem::new(ProblemKind::LibraryMissing, format!("缺少库 {}", artifact.name))
+                    .with_path(&artifact.path),
+            );
+            continue;
+        }
+        check_sha1(&artifact.path, sha1_by_name.get(artifact.name.as_str()).copied(), cache, problems);
+    }
+
+    problems.extend(check_natives(paths, version_json, &options.version_id, &features));
+
+    // 资源索引
+    let asset_index_id = version_json["assetIndex"]["id"]
+        .as_str()
+        .map(|id| id.to_string())
+        .or_else(|| options.asset_index_id.clone());
+    if let Some(asset_index_id) = asset_index_id {
+        let index_path = paths.assets_dir.join("indexes").join(format!("{}.json", asset_index_id));
+        if !index_path.is_file() {
+            problems.push(
+                PreflightProblem::new(ProblemKind::AssetIndexMissing, format!("缺少资源索引 {}", asset_index_id))
+                    .with_path(&index_path),
+            );
+        } else {
+            check_sha1(&index_path, version_json["assetIndex"]["sha1"].as_str(), cache, problems);
+        }
+    }
+}
+
+// 旧版本的natives需要提前解压，加载器版本的natives在父版本目录中
+fn check_natives(
+    paths: &MinecraftPaths,
+    version_json: &serde_json::Value,
+    version_id: &str,
+    features: &Features,
+) -> Option<PreflightProblem> {
+    let needs_natives = version_json["libraries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|library| library.get("natives").is_some() && rules_allow(library.get("rules"), features));
+    let natives_dir = natives_dir(paths, version_id);
+    let natives_extracted = std::fs::read_dir(&natives_dir)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    (needs_natives && !natives_extracted).then(|| {
+        PreflightProblem::new(ProblemKind::NativesMissing, "natives库未解压".to_string()).with_path(&natives_dir)
+    })
+}
+
+fn check_sha1(path: &Path, expected: Option<&str>, cache: &mut HashCache, problems: &mut Vec<PreflightProblem>) {
+    let Some(expected) = expected else {
+        return;
+    };
+    match cache.sha1(path) {
+        Ok(actual) if actual.eq_ignore_ascii_case(expected) => {}
+        Ok(_) => problems.push(
+            PreflightProblem::new(ProblemKind::FileCorrupted, format!("文件已损坏: {}", path.display())).with_path(path),
+        ),
+        Err(e) => problems.push(
+            PreflightProblem::new(ProblemKind::FileCorrupted, format!("无法读取 {}: {}", path.display(), e))
+                .with_path(path),
+        ),
+    }
+}
+
+fn check_memory(startup_parameter: &str, problems: &mut Vec<PreflightProblem>) {
+    let (Some(max_heap), Some(memory)) = (max_heap_mb(startup_parameter), get_system_memory()) else {
+        return;
+    };
+    if max_heap > memory.available_mb {
+        problems.push(PreflightProblem::new(
+            ProblemKind::InsufficientMemory,
+            format!("设置的最大内存为 {}MB，当前可用内存只有 {}MB", max_heap, memory.available_mb),
+        ));
+    }
+}
+
+// 参数中最后一个 -Xmx，单位MB
+pub fn max_heap_mb(startup_parameter: &str) -> Option<u64> {
+    startup_parameter
+        .split_whitespace()
+        .filter_map(|arg| arg.strip_prefix("-Xmx"))
+        .last()
+        .and_then(parse_size)
+        .map(|bytes| bytes / 1024 / 1024)
+}
+
+// 文件大小与修改时间不变时复用上次计算的sha1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedHash {
+    size: u64,
+    modified: u64, // 毫秒
+    sha1: String,
+}
+
+#[derive(Default)]
+struct HashCache {
+    entries: HashMap<String, CachedHash>,
+    changed: bool,
+}
+
+impl HashCache {
+    fn load() -> Self {
+        let entries = std::fs::read_to_string(get_launcher_dir().join(HASH_CACHE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { entries, changed: false }
+    }
+
+    fn save(&self) {
+        if !self.changed {
+            return;
+        }
+        let launcher_dir = get_launcher_dir();
+        let result = std::fs::create_dir_all(&launcher_dir).and_then(|_| {
+            let content = serde_json::to_string(&self.entries)?;
+            std::fs::write(launcher_dir.join(HASH_CACHE_FILE), content)
+        });
+        if let Err(e) = result {
+            println!("保存哈希缓存失败: {}", e);
+        }
+    }
+
+    fn sha1(&mut self, path: &Path) -> std::io::Result<String> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let key = path.to_string_lossy().into_owned();
+        if let Some(cached) = self.entries.get(&key) {
+            if cached.size == metadata.len() && cached.modified == modified {
+                return Ok(cached.sha1.clone());
+            }
+        }
+
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = sha1::Sha1::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        let sha1 = format!("{:x}", hasher.finalize());
+
+        self.entries.insert(
+            key,
+            CachedHash {
+                size: metadata.len(),
+                modified,
+                sha1: sha1.clone(),
+            },
+        );
+        self.changed = true;
+        Ok(sha1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_java_major_version() {
+        assert_eq!(java_major_version("1.8.0_392"), Some(8));
+        assert_eq!(java_major_version("17.0.2"), Some(17));
+        assert_eq!(java_major_version("21"), Some(21));
+        assert_eq!(java_major_version("22-ea"), Some(22));
+    }
+
+    #[test]
+    fn test_max_heap_mb() {
+        assert_eq!(max_heap_mb("-Xms1024m -Xmx4G"), Some(4096));
+        assert_eq!(max_heap_mb("-Xmx2048M -Xmx1g"), Some(1024));
+        assert_eq!(max_heap_mb("-Xmx1048576k"), Some(1024));
+        assert_eq!(max_heap_mb("-XX:+UseG1GC"), None);
+        assert_eq!(max_heap_mb("-Xmx99999999999999999G"), None);
+    }
+
+    #[test]
+    fn test_inherited_natives() {
+        let base_dir = std::env::temp_dir().join(format!("rtl-preflight-natives-{}", std::process::id()));
+        let paths = MinecraftPaths::from_base_dir(base_dir.clone());
+        let write_json = |id: &str, json: serde_json::Value| {
+            std::fs::create_dir_all(paths.get_version_dir(id)).unwrap();
+            std::fs::write(paths.get_version_json_path(id), json.to_string()).unwrap();
+        };
+        write_json(
+            "1.12.2",
+            serde_json::json!({
+                "id": "1.12.2",
+                "libraries": [{ "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4", "natives": { "linux": "natives-linux" } }]
+            }),
+        );
+        write_json(
+            "fabric-loader-1.12.2",
+            serde_json::json!({ "id": "fabric-loader-1.12.2", "inheritsFrom": "1.12.2", "libraries": [] }),
+        );
+
+        // natives只解压在原版目录中
+        let natives = paths.get_natives_dir("1.12.2");
+        std::fs::create_dir_all(&natives).unwrap();
+        std::fs::write(natives.join("liblwjgl.so"), "").unwrap();
+
+        let features = Features::new();
+        let version_json = resolve_version(&paths, "fabric-loader-1.12.2").unwrap();
+        assert!(check_natives(&paths, &version_json, "fabric-loader-1.12.2", &features).is_none());
+
+        std::fs::remove_dir_all(&natives).unwrap();
+        let problem = check_natives(&paths, &version_json, "fabric-loader-1.12.2", &features).unwrap();
+        assert_eq!(problem.kind, ProblemKind::NativesMissing);
+        assert_eq!(problem.path, Some(natives));
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_hash_cache() {
+        let dir = std::env::temp_dir().join("rtl-hash-cache-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("a.txt");
+        std::fs::write(&file, b"abc").unwrap();
+
+        let mut cache = HashCache::default();
+        assert_eq!(cache.sha1(&file).unwrap(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert!(cache.changed);
+
+        // 命中缓存时不重新计算
+        let key = file.to_string_lossy().into_owned();
+        cache.entries.get_mut(&key).unwrap().sha1 = "cached".to_string();
+        assert_eq!(cache.sha1(&file).unwrap(), "cached");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,365 @@
+// ***
+// 游戏进程管理，记录正在运行的游戏并在退出时通知前端
+// ***
+
+use super::game_log::capture;
+use super::hooks::run_hook;
+use super::stg_main::StartGame;
+use crate::module::crash::analyzer::{analyze_crash, CrashReport};
+use crate::module::crash::mappings::Mappings;
+use crate::module::download::paths::MinecraftPaths;
+use crate::module::instance::history::{record_session, SessionRecord};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::watch;
+
+// 检查进程是否退出的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 进程退出后等待日志读完的最长时间，游戏启动的子进程继承了管道时读取不会结束
+const READER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 正在运行的游戏
+#[derive(Debug, Clone, Serialize)]
+pub struct GameSession {
+    pub session_id: String,
+    pub pid: u32,
+    pub version_id: String,
+    pub instance_id: Option<String>,
+    pub started_at: u64, // unix时间戳，秒
+    pub game_dir: PathBuf,
+    pub log_file: PathBuf,
+    pub diagnostic: bool, // 诊断启动，退出时不会有崩溃分析，前端可忽略
+}
+
+// 游戏退出信息，同时作为 game-exit 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct GameExit {
+    pub session_id: String,
+    pub pid: u32,
+    pub exit_code: Option<i32>, // 被信号结束时为空
+    pub duration_secs: u64,
+    pub crash: Option<CrashReport>, // 非正常退出时的崩溃分析
+}
+
+struct RunningGame {
+    session: GameSession,
+    versions_dir: PathBuf,
+    versions: Vec<String>, // 使用中的版本，包括继承的父版本
+    child: Arc<Mutex<Child>>,
+    exit: watch::Receiver<Option<GameExit>>,
+}
+
+fn registry() -> &'static Mutex<HashMap<String, RunningGame>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, RunningGame>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 正在安装的版本，按 (versions目录, 版本id) 记录
+fn installing() -> &'static Mutex<HashSet<(PathBuf, String)>> {
+    static INSTALLING: OnceLock<Mutex<HashSet<(PathBuf, String)>>> = OnceLock::new();
+    INSTALLING.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn unix_now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+// 启动进程并登记，后台线程等待退出后移除记录并发送 game-exit 事件
+// temp_files 为本次启动生成的临时文件，游戏退出后删除
+pub fn spawn_game(
+    start_game: &StartGame,
+    mut command: Command,
+    temp_files: Vec<PathBuf>,
+    app: Option<AppHandle>,
+) -> Result<GameSession, String> {
+    // 检查与登记期间持有锁，避免与版本安装同时开始
+    let mut running = registry().lock().unwrap();
+    let installing_version = {
+        let installing = installing().lock().unwrap();
+        start_game
+            .version_chain
+            .iter()
+            .find(|version| installing.contains(&(start_game.versions_dir.clone(), version.to_string())))
+            .cloned()
+    };
+    if let Some(version) = installing_version {
+        remove_temp_files(&temp_files);
+        return Err(format!("版本 {} 正在安装，请等待安装完成", version));
+    }
+
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            remove_temp_files(&temp_files);
+            return Err(e.to_string());
+        }
+    };
+    let pid = child.id();
+    let now = unix_now();
+    let session_id = format!("{}-{}", now.as_millis(), pid);
+    let session = GameSession {
+        log_file: start_game.log_dir.join(format!("{}.log", session_id)),
+        session_id,
+        pid,
+        version_id: start_game.version_id.clone(),
+        instance_id: start_game.instance_id.clone(),
+        started_at: now.as_secs(),
+        game_dir: start_game.game_dir.clone(),
+        diagnostic: start_game.diagnostic,
+    };
+
+    // 日志文件创建失败不影响游戏运行
+    let log_file = std::fs::create_dir_all(&start_game.log_dir)
+        .and_then(|_| File::create(&session.log_file))
+        .map_err(|e| println!("创建游戏日志文件失败: {}", e))
+        .ok()
+        .map(|file| Arc::new(Mutex::new(file)));
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(capture(
+            session.session_id.clone(),
+            "stdout",
+            stdout,
+            log_file.clone(),
+            start_game.redactor.clone(),
+            app.clone(),
+        ));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(capture(
+            session.session_id.clone(),
+            "stderr",
+            stderr,
+            log_file,
+            start_game.redactor.clone(),
+            app.clone(),
+        ));
+    }
+
+    let child = Arc::new(Mutex::new(child));
+    let (exit_sender, exit_receiver) = watch::channel(None);
+    running.insert(
+        session.session_id.clone(),
+        RunningGame {
+            session: session.clone(),
+            versions_dir: start_game.versions_dir.clone(),
+            versions: start_game.version_chain.clone(),
+            child: child.clone(),
+            exit: exit_receiver,
+        },
+    );
+    drop(running);
+
+    let finished = session.clone();
+    let post_exit = start_game
+        .post_exit_command
+        .clone()
+        .map(|command| (command, start_game.hook_command_env()));
+    let redactor = start_game.redactor.clone();
+    let username = start_game.account.username.clone();
+    let java_path = start_game.java_path.clone();
+    let instance_dir = start_game.instance_dir.clone();
+    // 启动时的游戏目录，运行期间切换游戏目录不影响崩溃分析
+    let paths = start_game
+        .versions_dir
+        .parent()
+        .map(|base_dir| MinecraftPaths::from_base_dir(base_dir.to_path_buf()));
+    let started = Instant::now();
+    thread::spawn(move || {
+        let session = finished;
+        // 轮询而不是阻塞wait，以便 kill_game 能同时拿到进程
+        let status = loop {
+            match child.lock().unwrap().try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) => {}
+                Err(e) => {
+                    println!("等待游戏进程时出错: {}", e);
+                    break None;
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        // 等日志读完再通知退出，超时后不再等待，读取线程在管道关闭时自行结束
+        let deadline = Instant::now() + READER_TIMEOUT;
+        while readers.iter().any(|reader| !reader.is_finished()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+        }
+        for reader in readers.into_iter().filter(|reader| reader.is_finished()) {
+            let _ = reader.join();
+        }
+
+        let exit_code = status.and_then(|status| status.code());
+        println!("游戏进程已结束，进程ID: {}，退出码: {:?}", pid, exit_code);
+        // 被信号结束时没有退出码，同样视为崩溃
+        let crashed = exit_code != Some(0);
+        let crash = (crashed && !session.diagnostic).then(|| {
+            let mappings = paths
+                .as_ref()
+                .and_then(|paths| Mappings::for_version(paths, &session.version_id, false).ok());
+            let mut crash = analyze_crash(
+                &session.game_dir,
+                &session.log_file,
+                pid,
+                session.started_at,
+                exit_code,
+                mappings.as_ref(),
+            );
+            crash.redact(&redactor);
+            crash
+        });
+        let exit = GameExit {
+            session_id: session.session_id.clone(),
+            pid,
+            exit_code,
+            duration_secs: started.elapsed().as_secs(),
+            crash,
+        };
+
+        // 记录到实例的游戏历史
+        if let Some(instance_dir) = instance_dir.as_ref().filter(|_| !session.diagnostic) {
+            let record = SessionRecord {
+                session_id: session.session_id.clone(),
+                started_at: session.started_at,
+                ended_at: unix_now().as_secs(),
+                exit_code,
+                username,
+                java_path,
+                crashed,
+            };
+            if let Err(e) = record_session(instance_dir, record) {
+                println!("{}", e);
+            }
+        }
+
+        // 先通知退出再移除记录，期间调用 wait_game 仍能拿到退出信息
+        if let Some(app) = app {
+            let _ = app.emit("game-exit", exit.clone());
+        }
+        let _ = exit_sender.send(Some(exit));
+        registry().lock().unwrap().remove(&session.session_id);
+
+        // 退出后命令可能运行很久，在游戏移出运行列表后执行，不阻塞版本安装与 wait_game
+        // 临时文件在其后删除，命令仍可访问安全模式的覆盖目录
+        if let Some((post_exit_command, mut env)) = post_exit {
+            env.push((
+                "INST_EXIT_CODE".to_string(),
+                exit_code.map(|code| code.to_string()).unwrap_or_default(),
+            ));
+            if let Err(e) = run_hook("退出后命令", &post_exit_command, &session.game_dir, &env) {
+                println!("{}", e);
+            }
+        }
+        remove_temp_files(&temp_files);
+    });
+
+    Ok(session)
+}
+
+// 删除启动时生成的临时文件与目录，目录中的链接只删除链接本身
+pub fn remove_temp_files(temp_files: &[PathBuf]) {
+    for path in temp_files {
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        if let Err(e) = result {
+            println!("删除临时文件 {} 失败: {}", path.display(), e);
+        }
+    }
+}
+
+// 共享方法到前端
+#[tauri::command]
+pub fn list_running_games() -> Vec<GameSession> {
+    let mut sessions: Vec<GameSession> = registry()
+        .lock()
+        .unwrap()
+        .values()
+        .map(|game| game.session.clone())
+        .collect();
+    sessions.sort_by_key(|session| session.started_at);
+    sessions
+}
+
+// 版本正在被游戏使用时不允许重新安装或删除
+fn check_not_running(
+    registry: &HashMap<String, RunningGame>,
+    versions_dir: &Path,
+    version_id: &str,
+) -> Result<(), String> {
+    let running = registry.values().find(|game| {
+        game.versions_dir == versions_dir && game.versions.iter().any(|version| version == version_id)
+    });
+    match running {
+        Some(game) => Err(format!(
+            "版本 {} 正在被游戏使用（进程ID: {}），请先关闭游戏",
+            version_id, game.session.pid
+        )),
+        None => Ok(()),
+    }
+}
+
+// 安装或删除版本期间持有，释放前使用该版本的游戏无法启动
+pub struct VersionInstallGuard {
+    key: (PathBuf, String),
+}
+
+impl Drop for VersionInstallGuard {
+    fn drop(&mut self) {
+        installing().lock().unwrap().remove(&self.key);
+    }
+}
+
+// 开始安装或删除版本，版本正在被游戏使用或正在安装时失败
+pub fn lock_version_install(versions_dir: &Path, version_id: &str) -> Result<VersionInstallGuard, String> {
+    let registry = registry().lock().unwrap();
+    check_not_running(&registry, versions_dir, version_id)?;
+    let key = (versions_dir.to_path_buf(), version_id.to_string());
+    if !installing().lock().unwrap().insert(key.clone()) {
+        return Err(format!("版本 {} 正在安装", version_id));
+    }
+    Ok(VersionInstallGuard { key })
+}
+
+// 游戏是否仍在运行
+pub fn is_running(session_id: &str) -> bool {
+    registry().lock().unwrap().contains_key(session_id)
+}
+
+// 强制结束游戏，退出信息仍通过 game-exit 事件发送
+#[tauri::command]
+pub fn kill_game(session_id: String) -> Result<(), String> {
+    let child = registry()
+        .lock()
+        .unwrap()
+        .get(&session_id)
+        .map(|game| game.child.clone())
+        .ok_or_else(|| format!("游戏 {} 未在运行", session_id))?;
+    let result = child.lock().unwrap().kill();
+    result.map_err(|e| format!("结束游戏失败: {}", e))
+}
+
+// 等待游戏退出
+#[tauri::command]
+pub async fn wait_game(session_id: String) -> Result<GameExit, String> {
+    let mut exit = registry()
+        .lock()
+        .unwrap()
+        .get(&session_id)
+        .map(|game| game.exit.clone())
+        .ok_or_else(|| format!("游戏 {} 未在运行", session_id))?;
+    let result = exit
+        .wait_for(|exit| exit.is_some())
+        .await
+        .map_err(|_| format!("游戏 {} 的进程监视已中断", session_id))?;
+    Ok(result.clone().unwrap())
+}
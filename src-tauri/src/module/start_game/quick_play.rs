@@ -0,0 +1,134 @@
+// ***
+// 快速游戏，启动后直接进入存档、服务器或领域
+// ***
+
+use super::arguments::Features;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// 默认的服务器端口
+const DEFAULT_SERVER_PORT: u16 = 25565;
+
+// 快速游戏目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuickPlay {
+    Singleplayer { world: String },   // 存档文件夹名
+    Multiplayer { address: String },  // host[:port]
+    Realms { realm_id: String },
+}
+
+impl QuickPlay {
+    // 参数规则中对应的feature
+    pub fn add_features(&self, features: &mut Features) {
+        let feature = match self {
+            Self::Singleplayer { .. } => "is_quick_play_singleplayer",
+            Self::Multiplayer { .. } => "is_quick_play_multiplayer",
+            Self::Realms { .. } => "is_quick_play_realms",
+        };
+        features.insert(feature.to_string(), true);
+    }
+
+    // ${quickPlaySingleplayer} 等占位符
+    pub fn add_variables(&self, variables: &mut HashMap<&str, String>) {
+        match self {
+            Self::Singleplayer { world } => variables.insert("quickPlaySingleplayer", world.clone()),
+            Self::Multiplayer { address } => variables.insert("quickPlayMultiplayer", address.clone()),
+            Self::Realms { realm_id } => variables.insert("quickPlayRealms", realm_id.clone()),
+        };
+    }
+
+    // 1.20之前的版本只支持 --server/--port 直接进入服务器
+    pub fn legacy_arguments(&self) -> Option<Vec<String>> {
+        let Self::Multiplayer { address } = self else {
+            return None;
+        };
+        let (host, port) = split_server_address(address);
+        Some(vec![
+            "--server".to_string(),
+            host,
+            "--port".to_string(),
+            port.to_string(),
+        ])
+    }
+}
+
+// 拆分服务器地址，支持 [::1]:25565 形式的IPv6地址
+pub fn split_server_address(address: &str) -> (String, u16) {
+    let address = address.trim();
+    if let Some(rest) = address.strip_prefix('[') {
+        if let Some((host, port)) = rest.split_once(']') {
+            let port = port.strip_prefix(':').and_then(|port| port.parse().ok());
+            return (host.to_string(), port.unwrap_or(DEFAULT_SERVER_PORT));
+        }
+    }
+    match address.rsplit_once(':') {
+        // 没有方括号的IPv6地址不拆分端口
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host.to_string(), port),
+            Err(_) => (address.to_string(), DEFAULT_SERVER_PORT),
+        },
+        _ => (address.to_string(), DEFAULT_SERVER_PORT),
+    }
+}
+
+// 通过命令行参数启动的请求，由快捷方式使用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchRequest {
+    pub instance_id: String,
+    pub username: String,
+    pub quick_play: Option<QuickPlay>,
+}
+
+// --instance <id> --username <name> [--server <address> | --world <name> | --realm <id>]
+pub fn parse_launch_request(args: impl IntoIterator<Item = String>) -> Option<LaunchRequest> {
+    let mut instance_id = None;
+    let mut username = None;
+    let mut quick_play = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--instance" => instance_id = args.next(),
+            "--username" => username = args.next(),
+            "--server" => quick_play = args.next().map(|address| QuickPlay::Multiplayer { address }),
+            "--world" => quick_play = args.next().map(|world| QuickPlay::Singleplayer { world }),
+            "--realm" => quick_play = args.next().map(|realm_id| QuickPlay::Realms { realm_id }),
+            _ => {}
+        }
+    }
+
+    Some(LaunchRequest {
+        instance_id: instance_id?,
+        username: username.unwrap_or_else(|| "Player".to_string()),
+        quick_play,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_server_address() {
+        assert_eq!(split_server_address("mc.example.com"), ("mc.example.com".to_string(), 25565));
+        assert_eq!(split_server_address("mc.example.com:25566"), ("mc.example.com".to_string(), 25566));
+        assert_eq!(split_server_address("[::1]:25570"), ("::1".to_string(), 25570));
+        assert_eq!(split_server_address("::1"), ("::1".to_string(), 25565));
+    }
+
+    #[test]
+    fn test_parse_launch_request() {
+        let args = ["rtlauncher", "--instance", "survival", "--username", "Steve", "--server", "mc.example.com"];
+        let request = parse_launch_request(args.iter().map(|arg| arg.to_string())).unwrap();
+        assert_eq!(request.instance_id, "survival");
+        assert_eq!(request.username, "Steve");
+        assert_eq!(
+            request.quick_play,
+            Some(QuickPlay::Multiplayer {
+                address: "mc.example.com".to_string()
+            })
+        );
+        assert!(parse_launch_request(["rtlauncher".to_string()]).is_none());
+    }
+}
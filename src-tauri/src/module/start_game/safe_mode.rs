@@ -0,0 +1,131 @@
+// ***
+// 安全模式，通过临时的覆盖目录在不改动用户文件的情况下禁用模组或使用默认配置
+// ***
+
+use super::natives::session_temp_dir;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// 安全模式选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafeMode {
+    pub keep_mods: Vec<String>, // 保留的模组文件名，为空时不加载任何模组
+    pub fresh_config: bool,     // 使用空的 config 目录与默认的 options.txt
+}
+
+impl SafeMode {
+    // 覆盖目录中不链接到原目录的项
+    fn replaced_entries(&self) -> Vec<&'static str> {
+        let mut entries = vec!["mods"];
+        if self.fresh_config {
+            entries.extend(["config", "options.txt"]);
+        }
+        entries
+    }
+}
+
+// 在临时目录中创建覆盖目录，除被替换的项外都链接到原游戏目录，存档等改动仍保存在原处
+// 返回的目录在游戏退出后删除，删除链接不会影响原文件
+pub fn create_overlay(game_dir: &Path, safe_mode: &SafeMode) -> Result<PathBuf, String> {
+    let overlay = session_temp_dir("rtlauncher-safe");
+    // 目录已存在时失败，避免清理时删除其他游戏正在使用的覆盖目录
+    if let Some(parent) = overlay.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建安全模式目录失败: {}", e))?;
+    }
+    std::fs::create_dir(&overlay).map_err(|e| format!("创建安全模式目录失败: {}", e))?;
+
+    let result = fill_overlay(game_dir, &overlay, safe_mode);
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&overlay);
+        return Err(format!("创建安全模式目录失败: {}", e));
+    }
+    Ok(overlay)
+}
+
+fn fill_overlay(game_dir: &Path, overlay: &Path, safe_mode: &SafeMode) -> std::io::Result<()> {
+    let replaced = safe_mode.replaced_entries();
+    for entry in std::fs::read_dir(game_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if replaced.iter().any(|replaced| name == *replaced) {
+            continue;
+        }
+        link_entry(&entry.path(), &overlay.join(&name))?;
+    }
+
+    // 只链接保留的模组
+    let mods_dir = overlay.join("mods");
+    std::fs::create_dir_all(&mods_dir)?;
+    for name in &safe_mode.keep_mods {
+        let source = game_dir.join("mods").join(name);
+        // 文件名不能跳出mods目录
+        if Path::new(name).file_name().map_or(false, |file_name| file_name == name.as_str()) && source.is_file() {
+            link_entry(&source, &mods_dir.join(name))?;
+        }
+    }
+
+    if safe_mode.fresh_config {
+        std::fs::create_dir_all(overlay.join("config"))?;
+    }
+    Ok(())
+}
+
+// 链接文件或目录
+#[cfg(unix)]
+fn link_entry(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+// 符号链接需要管理员权限或开发者模式，目录使用联接，文件使用硬链接，跨分区时复制
+#[cfg(windows)]
+fn link_entry(source: &Path, target: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        let output = std::process::Command::new("cmd")
+            .args(["/C", "mklink", "/J"])
+            .arg(target)
+            .arg(source)
+            .output()?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(String::from_utf8_lossy(&output.stderr).to_string()));
+        }
+        Ok(())
+    } else {
+        std::fs::hard_link(source, target).or_else(|_| std::fs::copy(source, target).map(|_| ()))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay() {
+        let game_dir = std::env::temp_dir().join(format!("rtl-safe-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&game_dir);
+        for dir in ["mods", "config", "saves/world"] {
+            std::fs::create_dir_all(game_dir.join(dir)).unwrap();
+        }
+        for file in ["mods/a.jar", "mods/b.jar", "config/a.toml", "options.txt", "saves/world/level.dat"] {
+            std::fs::write(game_dir.join(file), file).unwrap();
+        }
+
+        let safe_mode = SafeMode {
+            keep_mods: vec!["b.jar".to_string(), "../options.txt".to_string()],
+            fresh_config: true,
+        };
+        let overlay = create_overlay(&game_dir, &safe_mode).unwrap();
+        assert!(!overlay.join("mods/a.jar").exists());
+        assert_eq!(std::fs::read_to_string(overlay.join("mods/b.jar")).unwrap(), "mods/b.jar");
+        assert!(!overlay.join("options.txt").exists());
+        assert!(!overlay.join("config/a.toml").exists());
+
+        // 存档写入原目录，删除覆盖目录后原文件不变
+        std::fs::write(overlay.join("saves/world/new.dat"), "new").unwrap();
+        std::fs::remove_dir_all(&overlay).unwrap();
+        assert!(game_dir.join("saves/world/new.dat").is_file());
+        assert!(game_dir.join("mods/a.jar").is_file());
+        assert!(game_dir.join("config/a.toml").is_file());
+        std::fs::remove_dir_all(&game_dir).unwrap();
+    }
+}
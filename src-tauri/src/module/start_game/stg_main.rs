@@ -2,34 +2,138 @@
 // 启动游戏主函数
 // ***
 
+use crate::api::account::Account;
 use crate::utils::get_java_path::get_java_path;
-use os_info;
+use std::collections::HashMap;
 use std::env::consts::OS;
 
-use crate::module::download::dwl_main::MinecraftPaths;
+use super::arguments::{collect_arguments, drop_empty_options, substitute, Features};
+use super::classpath::build_classpath;
+use super::argfile::{write_argfile, ARGFILE_THRESHOLD};
+use super::hooks::run_hook;
+use super::jvm_flags::{merge_flags, JvmPreset};
+use super::natives::create_session_natives;
+use super::preflight::{java_major_version, run_preflight};
+use super::process::{remove_temp_files, spawn_game, GameSession};
+use super::quick_play::{LaunchRequest, QuickPlay};
+use super::safe_mode::{create_overlay, SafeMode};
+use super::version::{natives_dir, resolve_version, version_chain};
+use crate::module::download::paths::MinecraftPaths;
+use crate::module::instance::instance::Instance;
+use crate::module::instance::memory::plan_instance_memory;
+use crate::utils::redact::Redactor;
+use crate::Setting::launcher_setting::{get_logs_dir, LauncherSetting};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use tauri::AppHandle;
+
+// 启动器名称与版本，对应 ${launcher_name} ${launcher_version}
+const LAUNCHER_NAME: &str = "RTL";
+const LAUNCHER_VERSION: &str = "0.1.1";
 
 // 启动游戏结构体
 pub struct StartGame {
     pub java_path: String,
     pub launch_args: Vec<String>,
+    pub main_class_end: usize, // launch_args中此位置之前为jvm参数与主类，可写入argfile
+    pub use_argfile: Option<bool>, // 为空时按命令长度自动决定
+    pub game_dir: PathBuf, // --gameDir 与工作目录
+    pub version_id: String,
+    pub version_chain: Vec<String>, // 启动的版本与继承的父版本，运行期间不允许重新安装或删除
+    pub versions_dir: PathBuf,      // 版本所在的versions目录，不同游戏目录中的同名版本互不影响
+    pub natives_dir: String,        // 版本的natives目录，启动时复制一份给本次游戏使用
+    pub instance_id: Option<String>,
+    pub instance_dir: Option<PathBuf>, // 启动时的实例目录，退出时在此记录游戏历史
+    pub log_dir: PathBuf, // 每次启动的日志保存位置
+    pub wrapper_command: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub hook_env: Vec<(String, String)>, // 只提供给启动前与退出后命令的 INST_* 变量
+    pub pre_launch_command: Option<String>,
+    pub post_exit_command: Option<String>,
+    pub account: Account,
+    pub redactor: Redactor, // 日志与崩溃报告中需要隐藏的令牌等信息
+    pub temp_files: Vec<PathBuf>, // 启动前创建的临时文件，如安全模式的覆盖目录，游戏退出后删除
+    pub diagnostic: bool, // 模组排查等诊断启动，退出时不分析崩溃也不记录游戏历史
+}
+
+// 生成启动参数所需的信息
+pub struct LaunchOptions {
+    pub startup_parameter: String,      // 用户的内存与jvm参数
+    pub version_id: String,
+    pub account: Account,
+    pub game_dir: PathBuf,
+    pub asset_index_id: Option<String>, // 版本json中没有assetIndex时使用
+    pub quick_play: Option<QuickPlay>,  // 启动后直接进入的存档或服务器
+    pub window_size: Option<(u32, u32)>, // 窗口宽高
+    pub fullscreen: bool,
+    pub jvm_preset: JvmPreset,
+    pub java_major: Option<u64>, // 所选Java的主版本号，用于选择与检查jvm参数
 }
 
 // 共享方法到前端
 #[tauri::command]
 pub async fn stg(
+    app: AppHandle,
     startup_parameter: String,
     version_id: String,
     java_version: String,
     asset_index_id: String,
     username: String,
-) -> Result<String, String> {
-    let start_game = StartGame::new(startup_parameter, version_id, java_version, asset_index_id, username);
-    match start_game.start_game() {
+    quick_play: Option<QuickPlay>,
+) -> Result<GameSession, String> {
+    // 查找Java、启动前检查与复制natives都会阻塞，在后台线程中进行
+    tokio::task::spawn_blocking(move || {
+        StartGame::new(
+            startup_parameter,
+            version_id,
+            java_version,
+            asset_index_id,
+            username,
+            quick_play,
+        )?
+        .start_game(Some(app))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("游戏启动失败: {}", e))
+}
 
-        Ok(output) => Ok(output),
-        Err(e) => Err(format!("游戏启动失败: {}", e)),
-    }
+// 按实例id启动
+#[tauri::command]
+pub async fn stg_instance(
+    app: AppHandle,
+    instance_id: String,
+    username: String,
+    quick_play: Option<QuickPlay>,
+    demo: Option<bool>,
+    safe_mode: Option<SafeMode>,
+) -> Result<GameSession, String> {
+    let instance = Instance::load(&instance_id)?;
+    let account = Account {
+        demo: demo.unwrap_or(false),
+        ..Account::offline(username)
+    };
+    tokio::task::spawn_blocking(move || {
+        let start_game = StartGame::from_instance(&instance, account, quick_play, safe_mode)?;
+        start_game
+            .start_game(Some(app))
+            .map_err(|e| format!("游戏启动失败: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 通过快捷方式的命令行参数启动，在后台线程中进行以免阻塞窗口创建
+pub fn launch_from_request(app: AppHandle, request: LaunchRequest) {
+    std::thread::spawn(move || {
+        let result = Instance::load(&request.instance_id).and_then(|instance| {
+            StartGame::from_instance(&instance, Account::offline(request.username), request.quick_play, None)?
+                .start_game(Some(app))
+        });
+        if let Err(e) = result {
+            println!("游戏启动失败: {}", e);
+        }
+    });
 }
 
 // 获取游戏jar路径
@@ -49,9 +153,200 @@ impl StartGame {
         java_version: String,
         asset_index_id: String,
         username: String,
-    ) -> Self {
+        quick_play: Option<QuickPlay>,
+    ) -> Result<Self, String> {
+        let java_path = Self::find_java(&java_version);
+        let java_major = Self::java_major(&java_path);
+        let options = LaunchOptions {
+            startup_parameter,
+            version_id,
+            account: Account::offline(username),
+            game_dir: MinecraftPaths::new().base_dir,
+            asset_index_id: Some(asset_index_id),
+            quick_play,
+            window_size: None,
+            fullscreen: false,
+            jvm_preset: JvmPreset::Default,
+            java_major,
+        };
+        Self::with_options(java_path, options)
+    }
+
+    // 从实例配置创建，--gameDir 指向实例目录，安全模式时指向临时的覆盖目录
+    pub fn from_instance(
+        instance: &Instance,
+        account: Account,
+        quick_play: Option<QuickPlay>,
+        safe_mode: Option<SafeMode>,
+    ) -> Result<Self, String> {
+        let (java_path, mut options) = Self::instance_options(instance, account)?;
+        options.quick_play = quick_play;
+        let overlay = match &safe_mode {
+            Some(safe_mode) => {
+                let overlay = create_overlay(&options.game_dir, safe_mode)?;
+                println!("安全模式启动，覆盖目录: {}", overlay.display());
+                options.game_dir = overlay.clone();
+                Some(overlay)
+            }
+            None => None,
+        };
+        let mut start_game = match Self::with_options(java_path, options) {
+            Ok(start_game) => start_game,
+            Err(e) => {
+                if let Some(overlay) = &overlay {
+                    let _ = std::fs::remove_dir_all(overlay);
+                }
+                return Err(e);
+            }
+        };
+        start_game.temp_files.extend(overlay);
+        start_game.instance_id = Some(instance.id.clone());
+        start_game.instance_dir = Some(instance.dir.clone());
+        start_game.log_dir = instance.dir.join("logs");
+        start_game.use_argfile = instance.config.use_argfile;
+
+        // 包装命令、环境变量与启动前后的命令
+        let config = &instance.config;
+        start_game.wrapper_command = config.wrapper_args();
+        start_game.env = config.env_vars();
+        start_game.hook_env = vec![
+            ("INST_ID".to_string(), instance.id.clone()),
+            ("INST_NAME".to_string(), config.name.clone()),
+            ("INST_DIR".to_string(), instance.dir.to_string_lossy().into_owned()),
+            ("INST_MC_DIR".to_string(), start_game.game_dir.to_string_lossy().into_owned()),
+            ("INST_JAVA".to_string(), start_game.java_path.clone()),
+            ("INST_VERSION".to_string(), start_game.version_id.clone()),
+        ];
+        start_game.pre_launch_command = config.pre_launch_command.clone().filter(|command| !command.trim().is_empty());
+        start_game.post_exit_command = config.post_exit_command.clone().filter(|command| !command.trim().is_empty());
+        Ok(start_game)
+    }
+
+    // 实例使用的Java与启动信息
+    pub fn instance_options(instance: &Instance, account: Account) -> Result<(String, LaunchOptions), String> {
+        let paths = MinecraftPaths::new();
+        let version_id = instance.config.launch_version().to_string();
+
+        // 未指定Java时按版本要求的主版本号查找
+        let java_path = match instance.config.java_executable() {
+            Some(java_path) => java_path.to_string_lossy().into_owned(),
+            None => {
+                let version_json = resolve_version(&paths, &version_id)?;
+                let java_version = version_json["javaVersion"]["majorVersion"]
+                    .as_u64()
+                    .map(|major| major.to_string())
+                    .unwrap_or_default();
+                Self::find_java(&java_version)
+            }
+        };
+
+        // 自动分配内存时手动设置的内存不生效
+        let startup_parameter = match plan_instance_memory(instance) {
+            Some(plan) => {
+                println!("自动分配内存: {}", plan.reasons.join("，"));
+                instance
+                    .config
+                    .startup_parameter_with_memory(Some(plan.min_mb), Some(plan.max_mb))
+            }
+            None => instance.config.startup_parameter(),
+        };
+
+        let options = LaunchOptions {
+            startup_parameter,
+            version_id,
+            account,
+            game_dir: instance.game_dir(),
+            asset_index_id: None,
+            quick_play: None,
+            window_size: instance.config.window_width.zip(instance.config.window_height),
+            fullscreen: instance.config.fullscreen,
+            jvm_preset: instance.config.jvm_preset,
+            java_major: Self::java_major(&java_path),
+        };
+        Ok((java_path, options))
+    }
+
+    // 启动前检查通过后生成启动参数
+    fn with_options(java_path: String, options: LaunchOptions) -> Result<Self, String> {
+        let blocking: Vec<String> = run_preflight(&java_path, &options)
+            .into_iter()
+            .filter(|problem| {
+                if !problem.blocking {
+                    println!("启动前检查警告: {}", problem.message);
+                }
+                problem.blocking
+            })
+            .map(|problem| problem.message)
+            .collect();
+        if !blocking.is_empty() {
+            return Err(format!("启动前检查未通过:\n{}", blocking.join("\n")));
+        }
+
+        let (launch_args, main_class_end) = Self::load_launch_args(&options)?;
+        let redactor = Self::launch_redactor(&options);
+        let paths = MinecraftPaths::new();
+        Ok(Self {
+            java_path,
+            launch_args,
+            main_class_end,
+            use_argfile: None,
+            game_dir: options.game_dir,
+            version_chain: version_chain(&paths, &options.version_id),
+            versions_dir: paths.versions_dir.clone(),
+            natives_dir: paths.get_absolute_path(natives_dir(&paths, &options.version_id)),
+            version_id: options.version_id,
+            instance_id: None,
+            instance_dir: None,
+            log_dir: get_logs_dir(),
+            wrapper_command: Vec::new(),
+            env: Vec::new(),
+            hook_env: Vec::new(),
+            pre_launch_command: None,
+            post_exit_command: None,
+            account: options.account,
+            redactor,
+            temp_files: Vec::new(),
+            diagnostic: false,
+        })
+    }
+
+    // 正版账号的令牌、代理账号密码，以及按设置隐藏的uuid
+    fn launch_redactor(options: &LaunchOptions) -> Redactor {
+        let account = &options.account;
+        let mut secrets = Vec::new();
+        // 离线账号的令牌就是uuid，不需要隐藏
+        if account.user_type != "legacy" {
+            secrets.push(account.access_token.clone());
+        }
+        if LauncherSetting::load().redact_uuid {
+            secrets.push(account.uuid.clone());
+            // 日志中的uuid通常带横线
+            if account.uuid.len() == 32 {
+                let uuid = &account.uuid;
+                secrets.push(format!(
+                    "{}-{}-{}-{}-{}",
+                    &uuid[0..8],
+                    &uuid[8..12],
+                    &uuid[12..16],
+                    &uuid[16..20],
+                    &uuid[20..]
+                ));
+            }
+        }
+        let mut redactor = Redactor::new(secrets);
+        let startup_args: Vec<String> = options
+            .startup_parameter
+            .split_whitespace()
+            .map(|s| s.to_string())
+            .collect();
+        redactor.add_proxy_credentials(&startup_args);
+        redactor
+    }
+
+    // 查找版本信息中包含java_version的Java
+    fn find_java(java_version: &str) -> String {
         let java_paths = get_java_path();
-        let java_path = java_paths
+        java_paths
             .iter()
             .find_map(|path| {
                 let possible_paths = match OS {
@@ -66,21 +361,14 @@ impl StartGame {
                 // 遍历所有可能的路径，检查Java版本
                 for p in possible_paths {
                     if let Ok(version) = Self::get_java_version(&p) {
-                        if version.contains(&java_version) {
+                        if version.contains(java_version) {
                             return Some(p);
                         }
                     }
                 }
                 None
             })
-            .unwrap_or_default();
-
-        let launch_args = Self::load_launch_args(startup_parameter, &version_id, &asset_index_id, username);
-
-        Self {
-            java_path,
-            launch_args,
-        }
+            .unwrap_or_default()
     }
 
     // 获取java版本
@@ -94,47 +382,23 @@ impl StartGame {
         Ok(version_info)
     }
 
-    pub fn load_launch_args(
-        startup_parameter: String,
-        version_id: &str,
-        asset_index_id: &str,
-        username: String,
-    ) -> Vec<String> {
-        let mut args = Vec::new();
-        let info = os_info::get();
-        let os_name = info.os_type().to_string();
-        let os_version = info.version().to_string();
-
-        // 获取路径管理结构体
-        let paths = MinecraftPaths::new();
-        // 获取日志配置文件路径
-        let log4j_config_path =
-            paths.get_absolute_path(paths.get_version_dir(version_id).join("client-1.12.xml"));
-        // 获取客户端jar路径
-        let game_jar_route = get_game_jar_path(version_id);
-        // 获取解压的natives目录路径
-        let natives_path = paths.get_absolute_path(
-            paths
-                .get_version_dir(version_id)
-                .join(format!("{}-natives", version_id)),
-        );
-        // 获取所有libraries的jar文件路径
-        let mut classpath = paths.get_libraries_classpath();
-        classpath.push(get_game_jar_path(version_id));
+    // 预设的jvm参数与用户参数，用户参数在后以便覆盖预设；JVM自检时也使用同一套参数
+    pub fn jvm_flags(preset: JvmPreset, java_major: Option<u64>, startup_parameter: &str) -> Vec<String> {
+        // 分割内存参数并添加到启动参数中
+        let user_flags: Vec<String> = startup_parameter.split_whitespace().map(|s| s.to_string()).collect();
+        merge_flags(preset.flags(java_major), &user_flags)
+    }
 
-        // 获取classpath路径
-        let libraries_path = if OS == "windows" {
-            format!("{}", classpath.join(";"))
-        } else {
-            format!("{}", classpath.join(":"))
-        };
+    // Java的主版本号，无法运行时为空
+    pub fn java_major(java_path: &str) -> Option<u64> {
+        let output = Self::get_java_version(java_path).ok()?;
+        let version = output.split('"').nth(1)?.to_string();
+        java_major_version(&version)
+    }
 
-        // 分割内存参数并添加到启动参数中
-        let memory_args: Vec<String> = startup_parameter
-            .split_whitespace()
-            .map(|s| s.to_string())
-            .collect();
-        args.extend(memory_args);
+    // 旧版本json没有 arguments.jvm 时使用的默认jvm参数
+    fn legacy_jvm_arguments() -> Vec<String> {
+        let mut args = Vec::new();
 
         // 检查是否为32位Windows系统
         let is_windows_32bit = OS == "windows" && cfg!(target_pointer_width = "32");
@@ -146,81 +410,280 @@ impl StartGame {
         if OS == "windows" {
             args.push("-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump".to_string());
         }
+        if OS == "macos" {
+            args.push("-XstartOnFirstThread".to_string());
+        }
 
-        // jvm参数
         args.extend(vec![
-            "-XX:+UseG1GC".to_string(),
-            "-XX:-UseAdaptiveSizePolicy".to_string(),
-            "-XX:-OmitStackTraceInFastThrow".to_string(),
-            format!("-Dos.name={}", os_name),
-            format!("-Dos.version={}", os_version),
-            "-Dminecraft.launcher.brand=RTL".to_string(),
-            "-Dminecraft.launcher.version=0.1.1".to_string(),
-            format!("-Dminecraft.client.jar={}", game_jar_route),
-            format!("-Dlog4j.configurationFile={}", log4j_config_path),
-            format!("-Djava.library.path={}", natives_path),
+            "-Djava.library.path=${natives_directory}".to_string(),
+            "-Dminecraft.launcher.brand=${launcher_name}".to_string(),
+            "-Dminecraft.launcher.version=${launcher_version}".to_string(),
             "-cp".to_string(),
-            libraries_path,
-            "net.minecraft.client.main.Main".to_string(),
-            "--version".to_string(),
-            version_id.to_string(),
-            "--username".to_string(),
-            username,
-            "--accessToken".to_string(),
-            "00000FFFFFFFFFFFFFFFFFFFFFF9E747".to_string(),
-            "--gameDir".to_string(),
-            paths.base_dir.to_string_lossy().into_owned(),
-            "--assetsDir".to_string(),
-            paths.assets_dir.to_string_lossy().into_owned(),
-            "--assetIndex".to_string(),
-            asset_index_id.to_string(),
+            "${classpath}".to_string(),
+        ]);
+        args
+    }
+
+    // 按版本json的参数模板生成完整启动参数，同时返回主类之后的位置
+    pub fn load_launch_args(options: &LaunchOptions) -> Result<(Vec<String>, usize), String> {
+        // 获取路径管理结构体
+        let paths = MinecraftPaths::new();
+        let version_id = options.version_id.as_str();
+        let version_json = resolve_version(&paths, version_id)?;
+        let features = Self::launch_features(options);
+
+        // 客户端jar所在的版本
+        let jar_id = version_json["jar"].as_str().unwrap_or(version_id).to_string();
+        // 获取客户端jar路径
+        let game_jar_route = get_game_jar_path(&jar_id);
+        // 获取解压的natives目录路径
+        let natives_path = paths.get_absolute_path(natives_dir(&paths, version_id));
+        // 版本选中的libraries的jar文件路径
+        let client_jar = paths.get_version_dir(&jar_id).join(format!("{}.jar", jar_id));
+        let classpath = build_classpath(&paths, &version_json, &features, client_jar)?;
+
+        // 获取classpath路径
+        let classpath_separator = if OS == "windows" { ";" } else { ":" };
+        let libraries_path = classpath.join(classpath_separator);
+
+        // 资源索引，1.6之前的版本使用虚拟资源目录
+        let asset_index_id = version_json["assetIndex"]["id"]
+            .as_str()
+            .map(|id| id.to_string())
+            .or_else(|| options.asset_index_id.clone())
+            .unwrap_or_else(|| "legacy".to_string());
+        let game_assets = if asset_index_id == "legacy" || asset_index_id == "pre-1.6" {
+            paths.assets_dir.join("virtual").join("legacy")
+        } else {
+            paths.assets_dir.clone()
+        };
 
+        let account = &options.account;
+        let game_dir = options.game_dir.to_string_lossy().into_owned();
+        let mut variables: HashMap<&str, String> = HashMap::from([
+            ("auth_player_name", account.username.clone()),
+            ("auth_uuid", account.uuid.clone()),
+            ("auth_access_token", account.access_token.clone()),
+            ("auth_session", format!("token:{}:{}", account.access_token, account.uuid)),
+            ("auth_xuid", account.xuid.clone()),
+            ("clientid", account.client_id.clone()),
+            ("user_type", account.user_type.clone()),
+            ("user_properties", "{}".to_string()),
+            ("version_name", version_id.to_string()),
+            ("version_type", version_json["type"].as_str().unwrap_or("release").to_string()),
+            ("game_directory", game_dir),
+            ("assets_root", paths.assets_dir.to_string_lossy().into_owned()),
+            ("game_assets", game_assets.to_string_lossy().into_owned()),
+            ("assets_index_name", asset_index_id),
+            ("natives_directory", natives_path),
+            ("launcher_name", LAUNCHER_NAME.to_string()),
+            ("launcher_version", LAUNCHER_VERSION.to_string()),
+            ("classpath", libraries_path),
+            ("classpath_separator", classpath_separator.to_string()),
+            ("library_directory", paths.libraries_dir.to_string_lossy().into_owned()),
+            ("primary_jar", game_jar_route.clone()),
+            ("primary_jar_name", format!("{}.jar", jar_id)),
         ]);
+        if let Some(quick_play) = &options.quick_play {
+            quick_play.add_variables(&mut variables);
+        }
+        if let Some((width, height)) = options.window_size {
+            variables.insert("resolution_width", width.to_string());
+            variables.insert("resolution_height", height.to_string());
+        }
 
-        args
+        let mut args = Vec::new();
+
+        // 版本json中的jvm参数，旧版本使用默认参数，加载器可能在其上追加
+        let is_legacy = version_json["minecraftArguments"].is_string()
+            || version_json["arguments"]["jvm"].as_array().is_none();
+        let mut jvm_template = if is_legacy {
+            Self::legacy_jvm_arguments()
+        } else {
+            Vec::new()
+        };
+        jvm_template.extend(collect_arguments(&version_json["arguments"]["jvm"], &features));
+        args.extend(jvm_template.iter().map(|arg| substitute(arg, &variables)));
+
+        // 日志配置文件
+        let logging = &version_json["logging"]["client"];
+        if let (Some(argument), Some(file_id)) = (logging["argument"].as_str(), logging["file"]["id"].as_str()) {
+            let log4j_config = [version_id, jar_id.as_str()]
+                .iter()
+                .filter_map(|id| paths.get_log_config_path(id, file_id))
+                .find(|path| path.is_file());
+            if let Some(log4j_config) = log4j_config {
+                args.push(argument.replace("${path}", &paths.get_absolute_path(log4j_config)));
+            }
+        }
+        args.push(format!("-Dminecraft.client.jar={}", game_jar_route));
+
+        // 启动器与用户的jvm参数
+        args.extend(Self::jvm_flags(options.jvm_preset, options.java_major, &options.startup_parameter));
+
+        // 主类
+        let main_class = version_json["mainClass"]
+            .as_str()
+            .ok_or_else(|| format!("版本 {} 缺少mainClass", version_id))?;
+        args.push(main_class.to_string());
+        let main_class_end = args.len();
+
+        // 游戏参数
+        let mut game_template: Vec<String> = version_json["minecraftArguments"]
+            .as_str()
+            .map(|legacy| legacy.split_whitespace().map(|s| s.to_string()).collect())
+            .unwrap_or_default();
+        game_template.extend(collect_arguments(&version_json["arguments"]["game"], &features));
+        let mut game_args: Vec<String> = game_template.iter().map(|arg| substitute(arg, &variables)).collect();
+
+        // 旧版本json没有按feature添加的参数，需要手动补上
+        let game_rules = version_json["arguments"]["game"].to_string();
+        if !game_rules.contains("${resolution_width}") {
+            if let Some((width, height)) = options.window_size {
+                game_args.extend(["--width".to_string(), width.to_string()]);
+                game_args.extend(["--height".to_string(), height.to_string()]);
+            }
+        }
+        if !game_rules.contains("--demo") && options.account.demo {
+            game_args.push("--demo".to_string());
+        }
+        if options.fullscreen && !game_args.iter().any(|arg| arg == "--fullscreen") {
+            game_args.push("--fullscreen".to_string());
+        }
+
+        // 不支持快速游戏的旧版本使用 --server/--port
+        let supports_quick_play = game_rules.contains("${quickPlayMultiplayer}");
+        if let Some(quick_play) = options.quick_play.as_ref().filter(|_| !supports_quick_play) {
+            match quick_play.legacy_arguments() {
+                Some(legacy) => game_args.extend(legacy),
+                None => println!("版本 {} 不支持快速游戏，已忽略", version_id),
+            }
+        }
+        args.extend(drop_empty_options(game_args));
+
+        Ok((args, main_class_end))
+    }
+
+    // 参数规则使用的feature
+    fn launch_features(options: &LaunchOptions) -> Features {
+        let mut features = Features::new();
+        if options.window_size.is_some() {
+            features.insert("has_custom_resolution".to_string(), true);
+        }
+        if options.account.demo {
+            features.insert("is_demo_user".to_string(), true);
+        }
+        if let Some(quick_play) = &options.quick_play {
+            quick_play.add_features(&mut features);
+        }
+        features
     }
 
-    pub fn start_game(&self) -> Result<String, String> {
-        let mut command = match OS {
-            "windows" | "linux" | "macos" => Command::new(&self.java_path),
-            _ => return Err("不支持的操作系统".to_string()),
+    // 需要使用argfile时写出文件，返回文件路径与替换后的参数
+    fn argfile_args(&self, launch_args: &[String]) -> Result<Option<(PathBuf, Vec<String>)>, String> {
+        let command_length: usize = launch_args.iter().map(|arg| arg.len() + 1).sum();
+        let wanted = self.use_argfile.unwrap_or(command_length > ARGFILE_THRESHOLD);
+        if !wanted {
+            return Ok(None);
+        }
+        let major = Self::java_major(&self.java_path);
+        if major.map_or(true, |major| major < 9) {
+            return Ok(None);
+        }
+
+        let (jvm_args, game_args) = launch_args.split_at(self.main_class_end);
+        let argfile = write_argfile(jvm_args).map_err(|e| format!("写入启动参数文件失败: {}", e))?;
+        let mut args = vec![format!("@{}", argfile.display())];
+        args.extend(game_args.iter().cloned());
+        Ok(Some((argfile, args)))
+    }
+
+    // 启动前与退出后命令的环境变量
+    pub fn hook_command_env(&self) -> Vec<(String, String)> {
+        self.env.iter().chain(&self.hook_env).cloned().collect()
+    }
+
+    // 启动游戏进程后立即返回，进程由 process 模块登记和等待
+    pub fn start_game(&self, app: Option<AppHandle>) -> Result<GameSession, String> {
+        if !matches!(OS, "windows" | "linux" | "macos") {
+            return Err("不支持的操作系统".to_string());
+        }
+
+        // 启动前命令失败时取消启动
+        if let Some(pre_launch_command) = &self.pre_launch_command {
+            if let Err(e) = run_hook("启动前命令", pre_launch_command, &self.game_dir, &self.hook_command_env()) {
+                remove_temp_files(&self.temp_files);
+                return Err(e);
+            }
+        }
+
+        // 有包装命令时由包装命令启动Java
+        let mut command = match self.wrapper_command.split_first() {
+            Some((wrapper, wrapper_args)) => {
+                let mut command = Command::new(wrapper);
+                command.args(wrapper_args).arg(&self.java_path);
+                command
+            }
+            None => Command::new(&self.java_path),
         };
 
-        // 设置工作目录为 .minecraft 文件夹
-        let paths = MinecraftPaths::new();
-        command.current_dir(&paths.base_dir);
+        // 设置工作目录为游戏目录
+        command.current_dir(&self.game_dir);
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+
+        // 每次启动使用单独的natives目录，同一版本可以同时运行多个
+        let mut temp_files = self.temp_files.clone();
+        let launch_args: Vec<String> = match create_session_natives(Path::new(&self.natives_dir)) {
+            Ok(Some(session_natives)) => {
+                let session_path = session_natives.to_string_lossy().into_owned();
+                temp_files.push(session_natives);
+                self.launch_args
+                    .iter()
+                    .map(|arg| arg.replace(&self.natives_dir, &session_path))
+                    .collect()
+            }
+            Ok(None) => self.launch_args.clone(),
+            Err(e) => {
+                println!("复制natives失败，使用版本目录中的natives: {}", e);
+                self.launch_args.clone()
+            }
+        };
 
-        command.args(&self.launch_args);
+        // 参数过长时把jvm参数写入argfile，Java 8不支持argfile
+        let java_args = match self.argfile_args(&launch_args) {
+            Ok(Some((argfile, java_args))) => {
+                println!("启动参数文件: {}", argfile.display());
+                temp_files.push(argfile);
+                java_args
+            }
+            Ok(None) => launch_args,
+            Err(e) => {
+                remove_temp_files(&temp_files);
+                return Err(e);
+            }
+        };
+        command.args(&java_args);
 
-        // 完整的启动命令
-        let full_command = format!("\"{}\" {}", &self.java_path, &self.launch_args.join(" "));
+        // 完整的启动命令，打印前隐藏令牌等信息
+        let printed_args = self.redactor.redact_args(&java_args);
+        let wrapper_prefix: String = self.wrapper_command.iter().map(|arg| format!("{} ", arg)).collect();
+        let full_command = format!("{}\"{}\" {}", wrapper_prefix, &self.java_path, &printed_args.join(" "));
         println!("完整启动命令: {}", full_command);
-        println!("工作目录: {}", paths.base_dir.display());
+        println!("工作目录: {}", self.game_dir.display());
 
         // 打印启动命令和参数
         println!("启动Java: {}", &self.java_path);
-        println!("启动参数: {:?}", &self.launch_args);
+        println!("启动参数: {:?}", &printed_args);
 
         // 启动游戏进程
-        match command.spawn() {
-            Ok(mut child) => {
-                println!("游戏启动成功，进程ID: {:?}", child.id());
-
-                // 等待游戏进程结束
-                match child.wait() {
-                    Ok(status) => {
-                        println!("游戏进程已结束，退出状态: {}", status);
-                        Ok("游戏已关闭".to_string())
-                    }
-                    Err(e) => {
-                        println!("等待游戏进程时出错: {}", e);
-                        Err(e.to_string())
-                    }
-                }
+        match spawn_game(self, command, temp_files, app) {
+            Ok(session) => {
+                println!("游戏启动成功，进程ID: {}", session.pid);
+                Ok(session)
             }
             Err(e) => {
                 println!("游戏启动失败: {}", e);
-                Err(e.to_string())
+                Err(e)
             }
         }
     }
@@ -0,0 +1,138 @@
+// ***
+// 版本json解析，合并 inheritsFrom
+// ***
+
+use crate::module::download::paths::MinecraftPaths;
+use serde_json::Value;
+use std::path::PathBuf;
+
+// 继承层数上限，防止循环继承
+const MAX_INHERIT_DEPTH: usize = 8;
+
+// 读取版本json并逐层合并父版本，结果中 jar 字段为客户端jar所在的版本id
+pub fn resolve_version(paths: &MinecraftPaths, version_id: &str) -> Result<Value, String> {
+    let mut json = paths.read_version_json(version_id)?;
+    if json.get("jar").and_then(|jar| jar.as_str()).is_none() {
+        json["jar"] = Value::String(version_id.to_string());
+    }
+
+    let mut depth = 0;
+    while let Some(parent_id) = json["inheritsFrom"].as_str().map(|id| id.to_string()) {
+        depth += 1;
+        if depth > MAX_INHERIT_DEPTH {
+            return Err(format!("版本 {} 的继承层数过多", version_id));
+        }
+        let parent = paths.read_version_json(&parent_id)?;
+        json = merge_version(parent, json, &parent_id);
+    }
+
+    Ok(json)
+}
+
+// 版本及其逐层继承的父版本id，启动时这些版本都在使用中
+pub fn version_chain(paths: &MinecraftPaths, version_id: &str) -> Vec<String> {
+    let mut chain = vec![version_id.to_string()];
+    while chain.len() <= MAX_INHERIT_DEPTH {
+        let parent = paths
+            .read_version_json(chain.last().unwrap())
+            .ok()
+            .and_then(|json| json["inheritsFrom"].as_str().map(|id| id.to_string()));
+        match parent {
+            Some(parent) if !chain.contains(&parent) => chain.push(parent),
+            _ => break,
+        }
+    }
+    chain
+}
+
+// natives只解压在继承链最底层的原版版本目录中，加载器版本使用父版本的natives
+pub fn natives_dir(paths: &MinecraftPaths, version_id: &str) -> PathBuf {
+    let chain = version_chain(paths, version_id);
+    paths.get_natives_dir(chain.last().map(|id| id.as_str()).unwrap_or(version_id))
+}
+
+// 子版本覆盖父版本的字段，libraries 与 arguments 则合并
+fn merge_version(mut parent: Value, child: Value, parent_id: &str) -> Value {
+    // 子版本没有客户端下载时使用父版本的jar
+    let jar = if child["downloads"]["client"].is_object() {
+        child["jar"].clone()
+    } else {
+        parent
+            .get("jar")
+            .cloned()
+            .unwrap_or_else(|| Value::String(parent_id.to_string()))
+    };
+    let grandparent = parent.get("inheritsFrom").cloned();
+
+    let Value::Object(child) = child else {
+        return parent;
+    };
+    for (key, value) in child {
+        match key.as_str() {
+            "inheritsFrom" | "jar" => {}
+            "libraries" => {
+                // 子版本的库优先
+                let mut libraries = value.as_array().cloned().unwrap_or_default();
+                libraries.extend(parent["libraries"].as_array().cloned().unwrap_or_default());
+                parent["libraries"] = Value::Array(libraries);
+            }
+            "arguments" => {
+                for kind in ["game", "jvm"] {
+                    if let Some(extra) = value[kind].as_array() {
+                        let mut arguments = parent["arguments"][kind].as_array().cloned().unwrap_or_default();
+                        arguments.extend(extra.iter().cloned());
+                        parent["arguments"][kind] = Value::Array(arguments);
+                    }
+                }
+            }
+            _ => {
+                parent[key] = value;
+            }
+        }
+    }
+
+    parent["jar"] = jar;
+    match grandparent {
+        Some(grandparent) => parent["inheritsFrom"] = grandparent,
+        None => {
+            if let Some(object) = parent.as_object_mut() {
+                object.remove("inheritsFrom");
+            }
+        }
+    }
+    parent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_version() {
+        let parent = json!({
+            "id": "1.20.1",
+            "mainClass": "net.minecraft.client.main.Main",
+            "downloads": { "client": { "url": "" } },
+            "libraries": [{ "name": "a:parent:1" }],
+            "arguments": { "game": ["--username"], "jvm": ["-cp"] }
+        });
+        let child = json!({
+            "id": "fabric-loader-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "jar": "fabric-loader-1.20.1",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "libraries": [{ "name": "b:child:1" }],
+            "arguments": { "jvm": ["-DFabricMcEmu=net.minecraft.client.main.Main"] }
+        });
+
+        let merged = merge_version(parent, child, "1.20.1");
+        assert_eq!(merged["id"], "fabric-loader-1.20.1");
+        assert_eq!(merged["jar"], "1.20.1");
+        assert_eq!(merged["mainClass"], "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(merged["libraries"][0]["name"], "b:child:1");
+        assert_eq!(merged["arguments"]["jvm"].as_array().unwrap().len(), 2);
+        assert_eq!(merged["arguments"]["game"].as_array().unwrap().len(), 1);
+        assert!(merged.get("inheritsFrom").is_none());
+    }
+}
@@ -0,0 +1,87 @@
+// ***
+// 创建直接进入服务器的快捷方式，通过命令行参数调用启动器
+// ***
+
+use crate::module::instance::instance::Instance;
+use std::env::consts::OS;
+
+#[tauri::command]
+pub fn create_server_shortcut(
+    instance_id: String,
+    username: String,
+    address: String,
+    output_path: String,
+) -> Result<String, String> {
+    let instance = Instance::load(&instance_id)?;
+    let launcher = std::env::current_exe().map_err(|e| format!("获取启动器路径失败: {}", e))?;
+    let args = [
+        launcher.to_string_lossy().into_owned(),
+        "--instance".to_string(),
+        instance.id.clone(),
+        "--username".to_string(),
+        username,
+        "--server".to_string(),
+        address.clone(),
+    ];
+
+    let content = match OS {
+        "windows" => {
+            let command: Vec<String> = args.iter().map(|arg| format!("\"{}\"", arg.replace('"', ""))).collect();
+            format!("@echo off\r\nstart \"\" {}\r\n", command.join(" "))
+        }
+        "linux" => {
+            let command: Vec<String> = args.iter().map(|arg| desktop_quote(arg)).collect();
+            format!(
+                "[Desktop Entry]\nType=Application\nName={} - {}\nExec={}\nTerminal=false\nCategories=Game;\n",
+                instance.config.name,
+                address,
+                command.join(" ")
+            )
+        }
+        _ => {
+            let command: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+            format!("#!/bin/sh\nexec {}\n", command.join(" "))
+        }
+    };
+
+    std::fs::write(&output_path, content).map_err(|e| format!("创建快捷方式失败: {}", e))?;
+
+    // .desktop 与 .command 需要可执行权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&output_path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("设置快捷方式权限失败: {}", e))?;
+    }
+
+    Ok("快捷方式已创建".to_string())
+}
+
+// sh的单引号转义
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+// .desktop 文件 Exec 字段的双引号转义
+fn desktop_quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted.replace('%', "%%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(desktop_quote("a \"b\" $c 100%"), "\"a \\\"b\\\" \\$c 100%%\"");
+    }
+}
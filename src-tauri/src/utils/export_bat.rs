@@ -6,6 +6,9 @@ use std::fs::File;
 use std::io::Write;
 use crate::module::start_game::stg_main::StartGame;
 
+// 脚本中代替访问令牌的环境变量，运行时再填入
+const TOKEN_VARIABLE: &str = "RTL_ACCESS_TOKEN";
+
 #[tauri::command]
 pub async fn export_bat(
     startup_parameter: String,
@@ -15,12 +18,34 @@ pub async fn export_bat(
     asset_index_id: String,
     username: String,
 ) -> Result<String, String> {
-    let start_game = StartGame::new(startup_parameter, version_id, java_version, asset_index_id, username);
-    let full_command = format!("\"{}\" {}", start_game.java_path, start_game.launch_args.join(" "));
+    let start_game = StartGame::new(startup_parameter, version_id, java_version, asset_index_id, username, None)?;
+
+    // 令牌不写入脚本，包括 ${auth_session} 中的部分
+    let access_token = &start_game.account.access_token;
+    let placeholder = format!("%{}%", TOKEN_VARIABLE);
+    let launch_args: Vec<String> = start_game
+        .launch_args
+        .iter()
+        .map(|arg| {
+            if access_token.is_empty() {
+                arg.clone()
+            } else {
+                arg.replace(access_token.as_str(), &placeholder)
+            }
+        })
+        .collect();
+    let full_command = format!("\"{}\" {}", start_game.java_path, launch_args.join(" "));
+
+    // 离线账号的令牌只是uuid，作为默认值；正版账号运行时输入
+    let token_line = if start_game.account.user_type == "legacy" {
+        format!("if not defined {0} set \"{0}={1}\"", TOKEN_VARIABLE, access_token)
+    } else {
+        format!("if not defined {0} set /p {0}=请输入访问令牌: ", TOKEN_VARIABLE)
+    };
 
     // 生成 .bat 文件内容
-    let content = format!("@echo off\r\n{}\r\npause\r\n", full_command);
-    
+    let content = format!("@echo off\r\n{}\r\n{}\r\npause\r\n", token_line, full_command);
+
     // 将内容写入指定的 .bat 文件
     match File::create(&output_path) {
         Ok(mut file) => {
@@ -30,7 +55,6 @@ pub async fn export_bat(
         },
         Err(e) => return Err(format!("创建文件失败: {}", e)),
     }
-    
+
     Ok("批处理文件已成功导出".to_string())
 }
-
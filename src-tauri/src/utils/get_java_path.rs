@@ -2,125 +2,279 @@
 // 获取java_home路径
 // ***
 
+use super::home_dir;
+use crate::Setting::launcher_setting::get_java_runtimes_dir;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::env::consts::OS;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use walkdir::WalkDir;
 
+// Java的发现来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JavaSource {
+    JavaHome,           // JAVA_HOME 环境变量
+    Where,              // windows 的 where java
+    MacJavaHome,        // macos 的 /usr/libexec/java_home
+    UpdateAlternatives, // linux 的 update-alternatives
+    Path,               // PATH 环境变量
+    Sdkman,             // ~/.sdkman/candidates/java
+    Asdf,               // ~/.asdf/installs/java
+    Jabba,              // ~/.jabba/jdk
+    IntellijJdks,       // ~/.jdks
+    GradleToolchains,   // ~/.gradle/jdks
+    SystemDir,          // 系统默认安装目录
+    Launcher,           // 启动器目录内的 runtimes，便携模式下随启动器移动
+}
+
+// 一个Java安装
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaInstallation {
+    pub home: String,       // java_home路径
+    pub executable: String, // 解析符号链接后的java可执行文件
+    pub source: JavaSource, // 首次发现它的来源
+}
 
 // 主方法
 #[tauri::command]
 pub fn get_java_path() -> Vec<String> {
-    let mut java_paths = Vec::new();
+    let mut java_paths: Vec<String> = get_java_installations()
+        .into_iter()
+        .map(|installation| installation.home)
+        .collect();
+    java_paths.sort();
+    java_paths.dedup();
+    java_paths
+}
+
+// 获取所有Java安装及其来源
+#[tauri::command]
+pub fn get_java_installations() -> Vec<JavaInstallation> {
+    let mut collector = JavaCollector::new();
 
     // 从环境变量获取 JAVA_HOME
     if let Ok(java_home) = std::env::var("JAVA_HOME") {
-        if Path::new(&java_home).exists() {
-            java_paths.push(java_home);
-        }
+        collector.add_home(Path::new(&java_home), JavaSource::JavaHome);
     }
 
+    // 启动器自带的运行时
+    scan_jdk_dir(&mut collector, &get_java_runtimes_dir(), 2, JavaSource::Launcher);
+
     // 根据不同操作系统自动获取 Java 路径
-    let mut system_paths = match OS {
-        "windows" => get_windows_java_path(),
-        "macos" => get_macos_java_path(),
-        "linux" => get_linux_java_path(),
-        _ => Vec::new(),
-    };
+    match OS {
+        "windows" => get_windows_java_path(&mut collector),
+        "macos" => get_macos_java_path(&mut collector),
+        "linux" => get_linux_java_path(&mut collector),
+        _ => {}
+    }
 
-    // 合并路径并去重
-    java_paths.append(&mut system_paths);
-    java_paths.sort();
-    java_paths.dedup();
-    java_paths
+    // PATH 中的java
+    scan_path_env(&mut collector);
+
+    // 各类工具链管理器
+    if OS != "windows" {
+        scan_toolchain_managers(&mut collector);
+    }
+
+    // 扫描文件系统查找其他安装
+    scan_for_java_installation(&mut collector);
+
+    collector.found
 }
 
-// 获取windows的java_home路径
-#[allow(dead_code)]
-fn get_windows_java_path() -> Vec<String> {
-    let mut paths = Vec::new();
+// 按解析后的可执行文件去重的收集器
+struct JavaCollector {
+    found: Vec<JavaInstallation>,
+    seen: HashSet<PathBuf>,
+}
+
+impl JavaCollector {
+    fn new() -> Self {
+        Self {
+            found: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    // 通过java_home添加
+    fn add_home(&mut self, home: &Path, source: JavaSource) {
+        if let Some(executable) = find_java_executable(home) {
+            self.add_executable(&executable, source);
+        }
+    }
+
+    // 通过java可执行文件添加，java_home由解析后的路径反推
+    fn add_executable(&mut self, executable: &Path, source: JavaSource) {
+        let Ok(canonical) = executable.canonicalize() else {
+            return;
+        };
+        if !canonical.is_file() || !self.seen.insert(canonical.clone()) {
+            return;
+        }
+
+        // <home>/bin/java
+        let Some(home) = canonical.parent().and_then(|bin| bin.parent()) else {
+            return;
+        };
+
+        self.found.push(JavaInstallation {
+            home: strip_verbatim_prefix(home),
+            executable: strip_verbatim_prefix(&canonical),
+            source,
+        });
+    }
+}
+
+// java可执行文件名
+fn java_executable_name() -> &'static str {
+    match OS {
+        "windows" => "java.exe",
+        _ => "java",
+    }
+}
+
+// 在java_home中查找可执行文件，兼容macos的 Contents/Home 结构
+fn find_java_executable(home: &Path) -> Option<PathBuf> {
+    [
+        home.join("bin").join(java_executable_name()),
+        home.join("Contents")
+            .join("Home")
+            .join("bin")
+            .join(java_executable_name()),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+// 去掉windows canonicalize产生的 \\?\ 前缀
+fn strip_verbatim_prefix(path: &Path) -> String {
+    path.to_string_lossy()
+        .trim_start_matches(r"\\?\")
+        .to_string()
+}
 
+// 获取windows的java_home路径
+fn get_windows_java_path(collector: &mut JavaCollector) {
     // 通过 where java 命令查找
     if let Ok(output) = Command::new("where").arg("java").output() {
         if let Ok(path) = String::from_utf8(output.stdout) {
             for java_path in path.lines() {
-                if let Some(parent) = Path::new(java_path).parent().and_then(|p| p.parent()) {
-                    paths.push(parent.to_string_lossy().into_owned());
-                }
+                collector.add_executable(Path::new(java_path.trim()), JavaSource::Where);
             }
         }
     }
-
-    // 扫描文件系统查找其他安装
-    let mut scan_paths = scan_for_java_installation();
-    paths.append(&mut scan_paths);
-    paths
 }
 
 // 获取macos的java_home路径
-#[allow(dead_code)]
-fn get_macos_java_path() -> Vec<String> {
-    let mut paths = Vec::new();
-
+fn get_macos_java_path(collector: &mut JavaCollector) {
     // 通过 /usr/libexec/java_home -V 命令获取所有版本
-    if let Ok(output) = Command::new("/usr/libexec/java_home")
-        .arg("-V")
-        .output() {
+    if let Ok(output) = Command::new("/usr/libexec/java_home").arg("-V").output() {
         if let Ok(stderr) = String::from_utf8(output.stderr) {
             for line in stderr.lines() {
                 if line.contains("Java SE") {
                     if let Some(path) = line.split("at ").nth(1) {
                         let path = path.trim().trim_matches('"');
-                        if Path::new(path).exists() {
-                            paths.push(path.to_string());
-                        }
+                        collector.add_home(Path::new(path), JavaSource::MacJavaHome);
                     }
                 }
             }
         }
     }
-
-    // 扫描文件系统查找其他安装
-    let mut scan_paths = scan_for_java_installation();
-    paths.append(&mut scan_paths);
-    paths
 }
 
 // 获取linux的java_home路径
-#[allow(dead_code)]
-fn get_linux_java_path() -> Vec<String> {
-    let mut paths = Vec::new();
-
+fn get_linux_java_path(collector: &mut JavaCollector) {
     // 通过 update-alternatives --list java 获取所有安装
     if let Ok(output) = Command::new("update-alternatives")
         .arg("--list")
         .arg("java")
-        .output() {
+        .output()
+    {
         if let Ok(path_list) = String::from_utf8(output.stdout) {
             for java_path in path_list.lines() {
-                if let Some(parent) = Path::new(java_path).parent().and_then(|p| p.parent()) {
-                    paths.push(parent.to_string_lossy().into_owned());
-                }
+                collector.add_executable(Path::new(java_path.trim()), JavaSource::UpdateAlternatives);
             }
         }
     }
+}
 
-    // 扫描文件系统查找其他安装
-    let mut scan_paths = scan_for_java_installation();
-    paths.append(&mut scan_paths);
-    paths
+// 扫描PATH环境变量
+fn scan_path_env(collector: &mut JavaCollector) {
+    if let Some(path_env) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path_env) {
+            collector.add_executable(&dir.join(java_executable_name()), JavaSource::Path);
+        }
+    }
+}
+
+// 扫描SDKMAN、asdf、jabba、IntelliJ和Gradle管理的JDK
+fn scan_toolchain_managers(collector: &mut JavaCollector) {
+    let home = home_dir();
+    let env_or_home = |var: &str, default: &str| -> Option<PathBuf> {
+        std::env::var_os(var)
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|h| h.join(default)))
+    };
+
+    let managers = [
+        (
+            env_or_home("SDKMAN_DIR", ".sdkman").map(|d| d.join("candidates").join("java")),
+            JavaSource::Sdkman,
+        ),
+        (
+            env_or_home("ASDF_DATA_DIR", ".asdf").map(|d| d.join("installs").join("java")),
+            JavaSource::Asdf,
+        ),
+        (
+            env_or_home("JABBA_HOME", ".jabba").map(|d| d.join("jdk")),
+            JavaSource::Jabba,
+        ),
+        (home.as_ref().map(|h| h.join(".jdks")), JavaSource::IntellijJdks),
+        (
+            env_or_home("GRADLE_USER_HOME", ".gradle").map(|d| d.join("jdks")),
+            JavaSource::GradleToolchains,
+        ),
+    ];
+
+    for (dir, source) in managers {
+        if let Some(dir) = dir {
+            // Gradle会在下载目录内再嵌套一层jdk目录
+            scan_jdk_dir(collector, &dir, 2, source);
+        }
+    }
+}
+
+// 扫描目录下的JDK，depth为java_home相对于dir的最大深度
+fn scan_jdk_dir(collector: &mut JavaCollector, dir: &Path, depth: usize, source: JavaSource) {
+    if !dir.is_dir() {
+        return;
+    }
+
+    let walker = WalkDir::new(dir)
+        .follow_links(true)
+        .min_depth(1)
+        .max_depth(depth)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'));
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        if entry.file_type().is_dir() {
+            collector.add_home(entry.path(), source);
+        }
+    }
 }
 
 // 通用的Java安装扫描函数
-fn scan_for_java_installation() -> Vec<String> {
-    let mut paths = Vec::new();
+fn scan_for_java_installation(collector: &mut JavaCollector) {
     let search_paths = match OS {
         "windows" => vec![
             "C:\\Program Files\\Java",
             "C:\\Program Files (x86)\\Java",
             "D:\\Program Files\\Java",
             "D:\\Program Files (x86)\\Java",
-            
         ],
         "macos" => vec![
             "/Library/Java/JavaVirtualMachines",
@@ -128,48 +282,41 @@ fn scan_for_java_installation() -> Vec<String> {
             "/usr/local/opt/java",
             "/opt/homebrew/opt/java",
         ],
-        "linux" => vec![
-            "/usr/lib/jvm",
-            "/usr/java",
-            "/opt/java",
-            "/usr/local/java",
-        ],
+        "linux" => vec!["/usr/lib/jvm", "/usr/java", "/opt/java", "/usr/local/java"],
         _ => vec![],
     };
 
     for base_path in search_paths {
-        if !Path::new(base_path).exists() {
+        let base_path = Path::new(base_path);
+        if !base_path.exists() {
             continue;
         }
 
+        // 目录本身也可能就是java_home
+        collector.add_home(base_path, JavaSource::SystemDir);
+
         let walker = WalkDir::new(base_path)
             .follow_links(true)
+            .min_depth(1)
             .max_depth(3)
+            .sort_by_file_name()
             .into_iter()
             .filter_entry(|e| {
                 let file_name = e.file_name().to_string_lossy().to_lowercase();
-                !file_name.starts_with(".") && 
-                (file_name.contains("jdk") || 
-                 file_name.contains("jre") || 
-                 file_name.contains("java"))
+                !file_name.starts_with('.')
+                    && (file_name.contains("jdk")
+                        || file_name.contains("jre")
+                        || file_name.contains("java")
+                        || file_name == "contents"
+                        || file_name == "home")
             });
 
         for entry in walker.filter_map(|e| e.ok()) {
-            let path = entry.path();
-            if path.is_dir() {
-                let java_exec = match OS {
-                    "windows" => path.join("bin").join("java.exe"),
-                    _ => path.join("bin").join("java"),
-                };
-
-                if java_exec.exists() {
-                    paths.push(path.to_string_lossy().into_owned());
-                }
+            if entry.file_type().is_dir() {
+                collector.add_home(entry.path(), JavaSource::SystemDir);
             }
         }
     }
-
-    paths
 }
 
 // 测试
@@ -184,4 +331,34 @@ mod tests {
             println!("Found Java at: {}", path);
         }
     }
+
+    #[test]
+    fn test_get_java_installations() {
+        for installation in get_java_installations() {
+            println!(
+                "Found Java at: {} ({:?})",
+                installation.executable, installation.source
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collector_dedup_symlink() {
+        let root = std::env::temp_dir().join(format!("rtl-java-scan-{}", std::process::id()));
+        let jdk = root.join("jdk-21");
+        std::fs::create_dir_all(jdk.join("bin")).unwrap();
+        std::fs::write(jdk.join("bin").join("java"), "").unwrap();
+        std::os::unix::fs::symlink(&jdk, root.join("current")).unwrap();
+
+        let mut collector = JavaCollector::new();
+        scan_jdk_dir(&mut collector, &root, 1, JavaSource::Sdkman);
+        collector.add_executable(&root.join("current").join("bin").join("java"), JavaSource::Path);
+
+        assert_eq!(collector.found.len(), 1);
+        assert_eq!(collector.found[0].source, JavaSource::Sdkman);
+        assert!(collector.found[0].home.ends_with("jdk-21"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -1,3 +1,16 @@
 pub mod request;
 pub mod get_java_path;
 pub mod export_bat;
+pub mod test_java;
+pub mod system_memory;
+pub mod create_shortcut;
+pub mod redact;
+
+use std::path::PathBuf;
+
+// 用户主目录
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
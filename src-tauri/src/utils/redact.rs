@@ -0,0 +1,106 @@
+// ***
+// 日志与导出内容中的敏感信息脱敏
+// ***
+
+// 替换后的内容
+const MASK: &str = "******";
+// 过短的值替换后会误伤普通文本
+const MIN_SECRET_LEN: usize = 6;
+
+// 下一个参数为敏感值的游戏参数
+const SECRET_OPTIONS: [&str; 4] = ["--accessToken", "--session", "--proxyPass", "--proxyUser"];
+// 值为敏感信息的系统属性
+const SECRET_PROPERTIES: [&str; 4] = [
+    "-Dhttp.proxyUser=",
+    "-Dhttp.proxyPassword=",
+    "-Dhttps.proxyUser=",
+    "-Dhttps.proxyPassword=",
+];
+
+// 记录本次启动中的敏感值，用于替换任意文本
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new(secrets: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .filter(|secret| secret.len() >= MIN_SECRET_LEN)
+            .collect();
+        // 先替换较长的值，避免只替换掉一部分
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets.dedup();
+        Self { secrets }
+    }
+
+    // 从jvm参数中找出代理账号与密码
+    pub fn add_proxy_credentials(&mut self, args: &[String]) {
+        let values: Vec<String> = args
+            .iter()
+            .filter_map(|arg| SECRET_PROPERTIES.iter().find_map(|prefix| arg.strip_prefix(prefix)))
+            .map(|value| value.to_string())
+            .collect();
+        *self = Self::new(self.secrets.drain(..).chain(values));
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for secret in &self.secrets {
+            if text.contains(secret.as_str()) {
+                text = text.replace(secret.as_str(), MASK);
+            }
+        }
+        text
+    }
+
+    // 启动参数脱敏，敏感参数的值即使没有登记也会被替换
+    pub fn redact_args(&self, args: &[String]) -> Vec<String> {
+        let mut result = Vec::with_capacity(args.len());
+        let mut mask_next = false;
+        for arg in args {
+            if mask_next {
+                result.push(MASK.to_string());
+                mask_next = false;
+                continue;
+            }
+            if let Some(prefix) = SECRET_PROPERTIES.iter().find(|prefix| arg.starts_with(*prefix)) {
+                result.push(format!("{}{}", prefix, MASK));
+                continue;
+            }
+            mask_next = SECRET_OPTIONS.contains(&arg.as_str());
+            result.push(self.redact(arg));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_args() {
+        let mut redactor = Redactor::new(["eyJhbGciOi.token".to_string(), "abc".to_string()]);
+        let args: Vec<String> = [
+            "-Dhttps.proxyPassword=hunter22",
+            "--username",
+            "Steve",
+            "--accessToken",
+            "eyJhbGciOi.token",
+            "--session",
+            "token:eyJhbGciOi.token:uuid",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        redactor.add_proxy_credentials(&args);
+
+        assert_eq!(
+            redactor.redact_args(&args),
+            vec!["-Dhttps.proxyPassword=******", "--username", "Steve", "--accessToken", "******", "--session", "******"]
+        );
+        assert_eq!(redactor.redact("password hunter22 token eyJhbGciOi.token abc"), "password ****** token ****** abc");
+    }
+}
@@ -0,0 +1,115 @@
+// ***
+// 系统内存信息
+// ***
+
+use std::env::consts::OS;
+use std::process::Command;
+
+// 物理内存，单位MB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemMemory {
+    pub total_mb: u64,
+    pub available_mb: u64,
+}
+
+// 读取失败时返回None
+pub fn get_system_memory() -> Option<SystemMemory> {
+    match OS {
+        "linux" => {
+            let content = std::fs::read_to_string("/proc/meminfo").ok()?;
+            parse_meminfo(&content)
+        }
+        "macos" => macos_memory(),
+        "windows" => windows_memory(),
+        _ => None,
+    }
+}
+
+// /proc/meminfo 中的数值单位为kB
+fn parse_meminfo(content: &str) -> Option<SystemMemory> {
+    let value = |key: &str| {
+        content.lines().find_map(|line| {
+            let rest = line.strip_prefix(key)?.strip_prefix(':')?;
+            rest.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let total = value("MemTotal")?;
+    // 旧内核没有MemAvailable
+    let available = value("MemAvailable")
+        .or_else(|| Some(value("MemFree")? + value("Cached").unwrap_or(0)))?;
+    Some(SystemMemory {
+        total_mb: total / 1024,
+        available_mb: available / 1024,
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// 可用内存按空闲、不活跃与可清除的页计算
+fn macos_memory() -> Option<SystemMemory> {
+    let total: u64 = command_output("sysctl", &["-n", "hw.memsize"])?.trim().parse().ok()?;
+    let vm_stat = command_output("vm_stat", &[])?;
+    let page_size = vm_stat
+        .lines()
+        .next()
+        .and_then(|line| line.split("page size of ").nth(1))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|size| size.parse::<u64>().ok())
+        .unwrap_or(4096);
+    let pages = |key: &str| {
+        vm_stat.lines().find_map(|line| {
+            let rest = line.strip_prefix(key)?.strip_prefix(':')?;
+            rest.trim().trim_end_matches('.').parse::<u64>().ok()
+        })
+    };
+    let available_pages = pages("Pages free").unwrap_or(0)
+        + pages("Pages inactive").unwrap_or(0)
+        + pages("Pages purgeable").unwrap_or(0);
+    Some(SystemMemory {
+        total_mb: total / 1024 / 1024,
+        available_mb: available_pages * page_size / 1024 / 1024,
+    })
+}
+
+// Win32_OperatingSystem 中的数值单位为KB
+fn windows_memory() -> Option<SystemMemory> {
+    let output = command_output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "$os = Get-CimInstance Win32_OperatingSystem; \"$($os.TotalVisibleMemorySize) $($os.FreePhysicalMemory)\"",
+        ],
+    )?;
+    let mut values = output.split_whitespace().map(|value| value.parse::<u64>().ok());
+    let total = values.next()??;
+    let available = values.next()??;
+    Some(SystemMemory {
+        total_mb: total / 1024,
+        available_mb: available / 1024,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let content = "MemTotal:       16318412 kB\nMemFree:         1024000 kB\nMemAvailable:    8159206 kB\nCached:          4096000 kB\n";
+        assert_eq!(
+            parse_meminfo(content),
+            Some(SystemMemory {
+                total_mb: 15935,
+                available_mb: 7967,
+            })
+        );
+        assert_eq!(parse_meminfo("MemTotal: 2048 kB\nMemFree: 1024 kB\n").unwrap().available_mb, 1);
+    }
+}
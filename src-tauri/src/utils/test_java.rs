@@ -0,0 +1,342 @@
+// ***
+// 启动前的JVM自检
+// ***
+
+use crate::api::account::Account;
+use crate::module::instance::instance::Instance;
+use crate::module::start_game::jvm_flags::JvmPreset;
+use crate::module::start_game::stg_main::StartGame;
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// 内置探针，源码见 resources/probe/RTLProbe.java
+const PROBE_CLASS: &[u8] = include_bytes!("../../resources/probe/RTLProbe.class");
+const PROBE_MAIN_CLASS: &str = "RTLProbe";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+// 同时进行多次自检时区分探针目录
+static PROBE_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+// 自检失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JavaTestFailure {
+    NotFound,                // 可执行文件不存在
+    PermissionDenied,        // 没有执行权限
+    WrongArchitecture,       // 架构与系统不符
+    MissingLibrary,          // 缺少libjli等动态库
+    HeapTooLarge,            // 无法分配-Xmx指定的堆
+    InvalidMemorySetting,    // 内存参数格式错误或Xms大于Xmx
+    UnrecognizedOption,      // 不支持的jvm参数
+    UnsupportedClassVersion, // Java版本过旧
+    VmInitFailed,            // 其他虚拟机初始化错误
+    Timeout,                 // 探针运行超时
+    Unknown,
+}
+
+impl JavaTestFailure {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::NotFound => "找不到Java可执行文件",
+            Self::PermissionDenied => "Java可执行文件没有执行权限",
+            Self::WrongArchitecture => "Java的CPU架构与当前系统不符",
+            Self::MissingLibrary => "Java缺少必要的动态库(如libjli)，安装可能已损坏",
+            Self::HeapTooLarge => "无法分配所设置的最大内存，请降低-Xmx或使用64位Java",
+            Self::InvalidMemorySetting => "内存参数无效，请检查-Xms/-Xmx的格式与大小关系",
+            Self::UnrecognizedOption => "当前Java不支持所设置的JVM参数",
+            Self::UnsupportedClassVersion => "Java版本过旧",
+            Self::VmInitFailed => "Java虚拟机初始化失败",
+            Self::Timeout => "Java自检超时",
+            Self::Unknown => "Java自检失败",
+        }
+    }
+}
+
+// 自检结果
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaTestResult {
+    pub success: bool,
+    pub java_version: Option<String>,
+    pub java_vendor: Option<String>,
+    pub os_arch: Option<String>,
+    pub data_model: Option<String>,
+    pub max_memory: Option<u64>, // 字节
+    pub failure: Option<JavaTestFailure>,
+    pub message: String,
+    pub output: String, // 探针的stderr输出
+}
+
+impl JavaTestResult {
+    fn failed(failure: JavaTestFailure, output: String) -> Self {
+        Self {
+            success: false,
+            java_version: None,
+            java_vendor: None,
+            os_arch: None,
+            data_model: None,
+            max_memory: None,
+            failure: Some(failure),
+            message: failure.message().to_string(),
+            output,
+        }
+    }
+}
+
+// 共享方法到前端
+// 指定实例时使用与启动相同的内存分配、jvm参数、包装命令与环境变量，java_path 为空时使用实例的Java
+#[tauri::command]
+pub async fn test_java(
+    java_path: String,
+    startup_parameter: String,
+    jvm_preset: Option<JvmPreset>,
+    instance_id: Option<String>,
+) -> Result<JavaTestResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let Some(instance_id) = instance_id else {
+            let jvm_flags = StartGame::jvm_flags(jvm_preset.unwrap_or_default(), None, &startup_parameter);
+            return run_probe(&java_path, &jvm_flags, &[], &[]);
+        };
+        let instance = Instance::load(&instance_id)?;
+        let (instance_java, options) = StartGame::instance_options(&instance, Account::default())?;
+        let (java_path, java_major) = if java_path.is_empty() {
+            (instance_java, options.java_major)
+        } else {
+            let java_major = StartGame::java_major(&java_path);
+            (java_path, java_major)
+        };
+        let jvm_flags = StartGame::jvm_flags(options.jvm_preset, java_major, &options.startup_parameter);
+        run_probe(
+            &java_path,
+            &jvm_flags,
+            &instance.config.wrapper_args(),
+            &instance.config.env_vars(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// 使用给定的jvm参数运行探针，wrapper 与 env 与启动游戏时相同
+pub fn run_probe(
+    java_path: &str,
+    jvm_flags: &[String],
+    wrapper: &[String],
+    env: &[(String, String)],
+) -> Result<JavaTestResult, String> {
+    if java_path.is_empty() || !Path::new(java_path).is_file() {
+        return Ok(JavaTestResult::failed(JavaTestFailure::NotFound, String::new()));
+    }
+
+    // 每次自检使用单独的探针目录，结束后删除
+    let probe_dir = probe_dir();
+    std::fs::create_dir_all(&probe_dir).map_err(|e| format!("创建探针目录失败: {}", e))?;
+    let result = probe(java_path, jvm_flags, wrapper, env, &probe_dir);
+    let _ = std::fs::remove_dir_all(&probe_dir);
+    result
+}
+
+fn probe_dir() -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    std::env::temp_dir().join("rtlauncher-probe").join(format!(
+        "{}-{}-{}",
+        std::process::id(),
+        millis,
+        PROBE_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+fn probe(
+    java_path: &str,
+    jvm_flags: &[String],
+    wrapper: &[String],
+    env: &[(String, String)],
+    probe_dir: &Path,
+) -> Result<JavaTestResult, String> {
+    std::fs::write(probe_dir.join(format!("{}.class", PROBE_MAIN_CLASS)), PROBE_CLASS)
+        .map_err(|e| format!("写入探针失败: {}", e))?;
+
+    let mut command = match wrapper.split_first() {
+        Some((wrapper, wrapper_args)) => {
+            let mut command = Command::new(wrapper);
+            command.args(wrapper_args).arg(java_path);
+            command
+        }
+        None => Command::new(java_path),
+    };
+    let mut child = match command
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .args(jvm_flags)
+        .arg("-cp")
+        .arg(probe_dir)
+        .arg(PROBE_MAIN_CLASS)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Ok(JavaTestResult::failed(classify_spawn_error(&e), e.to_string())),
+    };
+
+    // 在等待期间读取输出，避免管道写满后探针阻塞
+    let stdout_reader = child.stdout.take().map(drain);
+    let stderr_reader = child.stderr.take().map(drain);
+    let collect = |reader: Option<JoinHandle<Vec<u8>>>| {
+        let bytes = reader.and_then(|reader| reader.join().ok()).unwrap_or_default();
+        String::from_utf8_lossy(&bytes).to_string()
+    };
+
+    // 等待探针结束，超时则结束进程
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() > PROBE_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(JavaTestResult::failed(JavaTestFailure::Timeout, collect(stderr_reader)));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    let stdout = collect(stdout_reader);
+    let stderr = collect(stderr_reader);
+
+    if !status.success() {
+        return Ok(JavaTestResult::failed(classify_failure(&stderr), stderr));
+    }
+
+    let value = |key: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(|v| v.trim().to_string())
+    };
+
+    Ok(JavaTestResult {
+        success: true,
+        java_version: value("java.version"),
+        java_vendor: value("java.vendor"),
+        os_arch: value("os.arch"),
+        data_model: value("data.model"),
+        max_memory: value("max.memory").and_then(|v| v.parse().ok()),
+        failure: None,
+        message: "Java自检通过".to_string(),
+        output: stderr,
+    })
+}
+
+// 在单独的线程中读取管道直到关闭
+fn drain<R: Read + Send + 'static>(mut pipe: R) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+// 进程无法启动时的原因
+fn classify_spawn_error(error: &std::io::Error) -> JavaTestFailure {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => JavaTestFailure::NotFound,
+        std::io::ErrorKind::PermissionDenied => JavaTestFailure::PermissionDenied,
+        _ => {
+            // ENOEXEC: Exec format error / windows ERROR_BAD_EXE_FORMAT
+            let message = error.to_string().to_lowercase();
+            if error.raw_os_error() == Some(8)
+                || error.raw_os_error() == Some(193)
+                || message.contains("exec format")
+                || message.contains("not a valid win32")
+            {
+                JavaTestFailure::WrongArchitecture
+            } else {
+                JavaTestFailure::Unknown
+            }
+        }
+    }
+}
+
+// 解析探针的错误输出
+pub fn classify_failure(stderr: &str) -> JavaTestFailure {
+    let text = stderr.to_lowercase();
+    let has = |patterns: &[&str]| patterns.iter().any(|p| text.contains(p));
+
+    if has(&[
+        "error while loading shared libraries",
+        "libjli",
+        "could not find libjava",
+        "error loading: ",
+        "could not load jvm.dll",
+        "error: could not find java se runtime environment",
+    ]) {
+        JavaTestFailure::MissingLibrary
+    } else if has(&["wrong elf class", "exec format error", "bad cpu type", "not a valid win32"]) {
+        JavaTestFailure::WrongArchitecture
+    } else if has(&[
+        "could not reserve enough space",
+        "exceeds the maximum representable size",
+        "the specified size exceeds",
+        "too small initial heap",
+        "outofmemoryerror",
+        "insufficient memory",
+    ]) {
+        JavaTestFailure::HeapTooLarge
+    } else if has(&[
+        "invalid maximum heap size",
+        "invalid initial heap size",
+        "larger value than the maximum heap size",
+        "incompatible minimum and maximum heap sizes",
+    ]) {
+        JavaTestFailure::InvalidMemorySetting
+    } else if has(&["unrecognized vm option", "unrecognized option", "must be enabled via -xx:+unlockexperimentalvmoptions"]) {
+        JavaTestFailure::UnrecognizedOption
+    } else if has(&["unsupportedclassversionerror", "unsupported major.minor version"]) {
+        JavaTestFailure::UnsupportedClassVersion
+    } else if has(&["error occurred during initialization of vm", "could not create the java virtual machine"]) {
+        JavaTestFailure::VmInitFailed
+    } else {
+        JavaTestFailure::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_failure() {
+        assert_eq!(
+            classify_failure("Error occurred during initialization of VM\nCould not reserve enough space for 8388608KB object heap"),
+            JavaTestFailure::HeapTooLarge
+        );
+        assert_eq!(
+            classify_failure("java: error while loading shared libraries: libjli.so: cannot open shared object file"),
+            JavaTestFailure::MissingLibrary
+        );
+        assert_eq!(
+            classify_failure("Unrecognized VM option 'UseZGC'\nError: Could not create the Java Virtual Machine."),
+            JavaTestFailure::UnrecognizedOption
+        );
+        assert_eq!(
+            classify_failure("Error occurred during initialization of VM\nInitial heap size set to a larger value than the maximum heap size"),
+            JavaTestFailure::InvalidMemorySetting
+        );
+    }
+
+    #[test]
+    fn test_run_probe() {
+        for installation in crate::utils::get_java_path::get_java_installations() {
+            let result = run_probe(&installation.executable, &StartGame::jvm_flags(JvmPreset::Default, None, "-Xmx256m"), &[], &[]).unwrap();
+            println!("{}: {:?}", installation.executable, result);
+        }
+    }
+}